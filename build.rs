@@ -0,0 +1,24 @@
+use std::process::Command;
+
+// Exposes the commit being built as GIT_SHA for the /version endpoint. A
+// GIT_SHA set in the environment wins, for builds made without a .git dir.
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+
+    let git_sha = std::env::var("GIT_SHA").ok().or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "--short", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|sha| sha.trim().to_string())
+    });
+
+    println!(
+        "cargo:rustc-env=GIT_SHA={}",
+        git_sha.unwrap_or_else(|| "unknown".to_string())
+    );
+}
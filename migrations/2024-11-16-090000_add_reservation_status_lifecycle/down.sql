@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE reservations
+DROP CONSTRAINT reservations_status_check,
+DROP COLUMN checked_in_by,
+DROP COLUMN checked_in_at,
+DROP COLUMN checked_out_by,
+DROP COLUMN checked_out_at,
+DROP COLUMN no_show_by,
+DROP COLUMN no_show_at,
+ALTER COLUMN confirmed_at SET DEFAULT NOW(),
+ALTER COLUMN cancelled_at SET DEFAULT NOW();
@@ -0,0 +1,40 @@
+-- Your SQL goes here
+
+-- status used to be free text, bring old spellings onto the six known values.
+-- Anything unrecognised goes back to pending for staff to review.
+UPDATE reservations
+SET status = REPLACE(REPLACE(LOWER(TRIM(status)), '-', '_'), ' ', '_');
+
+UPDATE reservations
+SET status = CASE status
+    WHEN 'canceled' THEN 'cancelled'
+    WHEN 'booked' THEN 'confirmed'
+    WHEN 'checkedin' THEN 'checked_in'
+    WHEN 'checkedout' THEN 'checked_out'
+    WHEN 'noshow' THEN 'no_show'
+    ELSE 'pending'
+END
+WHERE status NOT IN ('pending', 'confirmed', 'checked_in', 'checked_out', 'cancelled', 'no_show');
+
+-- The old NOW() defaults stamped every row, keep only the stamps that were
+-- really set alongside the staff member who did it
+UPDATE reservations
+SET confirmed_at = NULL
+WHERE confirmed_by IS NULL OR status = 'pending';
+
+UPDATE reservations
+SET cancelled_at = NULL
+WHERE cancelled_by IS NULL OR status <> 'cancelled';
+
+ALTER TABLE reservations
+ALTER COLUMN confirmed_at DROP DEFAULT,
+ALTER COLUMN cancelled_at DROP DEFAULT,
+ADD COLUMN checked_in_by INT REFERENCES staff(id),
+ADD COLUMN checked_in_at TIMESTAMPTZ,
+ADD COLUMN checked_out_by INT REFERENCES staff(id),
+ADD COLUMN checked_out_at TIMESTAMPTZ,
+ADD COLUMN no_show_by INT REFERENCES staff(id),
+ADD COLUMN no_show_at TIMESTAMPTZ,
+ADD CONSTRAINT reservations_status_check CHECK (
+    status IN ('pending', 'confirmed', 'checked_in', 'checked_out', 'cancelled', 'no_show')
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE reservations
+DROP COLUMN cancellation_reason;
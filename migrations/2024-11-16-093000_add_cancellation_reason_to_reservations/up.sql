@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE reservations
+ADD COLUMN cancellation_reason TEXT;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE reservations
+DROP CONSTRAINT reservations_no_overlapping_stays,
+DROP CONSTRAINT reservations_dates_check;
+
+DROP EXTENSION IF EXISTS btree_gist;
@@ -0,0 +1,24 @@
+-- Your SQL goes here
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+-- Dates were never checked before, daterange() rejects reversed bounds and
+-- an empty range would slip past the exclusion. Reversed stays were entered
+-- the wrong way round, same day stays become one night.
+UPDATE reservations
+SET check_in_date = check_out_date,
+    check_out_date = check_in_date
+WHERE check_out_date < check_in_date;
+
+UPDATE reservations
+SET check_out_date = check_in_date + 1
+WHERE check_out_date = check_in_date;
+
+ALTER TABLE reservations
+ADD CONSTRAINT reservations_dates_check CHECK (check_out_date > check_in_date);
+
+ALTER TABLE reservations
+ADD CONSTRAINT reservations_no_overlapping_stays
+EXCLUDE USING gist (
+    room_id WITH =,
+    daterange(check_in_date, check_out_date) WITH &&
+) WHERE (status IN ('pending', 'confirmed', 'checked_in'));
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX customer_contacts_phone_number_idx;
+DROP INDEX customer_contacts_email_idx;
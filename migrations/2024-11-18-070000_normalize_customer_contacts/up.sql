@@ -0,0 +1,7 @@
+-- Your SQL goes here
+UPDATE customer_contacts
+SET email = LOWER(TRIM(email)),
+    phone_number = REGEXP_REPLACE(phone_number, '[^0-9+]', '', 'g');
+
+CREATE INDEX customer_contacts_email_idx ON customer_contacts (email);
+CREATE INDEX customer_contacts_phone_number_idx ON customer_contacts (phone_number);
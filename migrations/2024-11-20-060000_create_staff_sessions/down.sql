@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE staff_sessions;
+
+ALTER TABLE staff
+DROP COLUMN is_active;
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+ALTER TABLE staff
+ADD COLUMN is_active BOOLEAN DEFAULT TRUE NOT NULL;
+
+CREATE TABLE staff_sessions (
+    id SERIAL PRIMARY KEY,
+    staff_id INT REFERENCES staff(id) NOT NULL,
+    refresh_token_hash VARCHAR(64) UNIQUE NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL,
+    updated_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
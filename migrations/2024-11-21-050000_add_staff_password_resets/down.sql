@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE staff_password_resets;
+
+ALTER TABLE staff
+DROP COLUMN must_change_password;
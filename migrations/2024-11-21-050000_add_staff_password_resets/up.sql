@@ -0,0 +1,12 @@
+-- Your SQL goes here
+ALTER TABLE staff
+ADD COLUMN must_change_password BOOLEAN DEFAULT FALSE NOT NULL;
+
+CREATE TABLE staff_password_resets (
+    id SERIAL PRIMARY KEY,
+    staff_id INT REFERENCES staff(id) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE login_ip_attempts;
+
+ALTER TABLE staff
+DROP COLUMN failed_login_attempts,
+DROP COLUMN locked_until;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+ALTER TABLE staff
+ADD COLUMN failed_login_attempts INT DEFAULT 0 NOT NULL,
+ADD COLUMN locked_until TIMESTAMPTZ;
+
+CREATE TABLE login_ip_attempts (
+    ip_address VARCHAR(45) PRIMARY KEY,
+    failed_attempts INT DEFAULT 0 NOT NULL,
+    locked_until TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
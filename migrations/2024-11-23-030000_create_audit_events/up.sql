@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE audit_events (
+    id SERIAL PRIMARY KEY,
+    actor_id INT REFERENCES staff(id),
+    entity VARCHAR(50) NOT NULL,
+    entity_id INT NOT NULL,
+    action VARCHAR(50) NOT NULL,
+    before JSONB,
+    after JSONB,
+    created_at TIMESTAMPTZ DEFAULT NOW() NOT NULL
+);
+
+CREATE INDEX audit_events_entity_idx ON audit_events (entity, entity_id, created_at);
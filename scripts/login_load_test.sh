@@ -0,0 +1,57 @@
+#!/usr/bin/env bash
+# Keeps the server busy with concurrent logins (bcrypt + database) and times
+# an unrelated cheap request meanwhile. Probe latency should stay close to its
+# idle value; if it climbs towards the login latency, blocking work is running
+# on the async workers again. Exits non-zero when the slowest probe taken
+# during the logins goes over MAX_PROBE_SECONDS.
+#
+# Usage: EMAIL=staff@example.com PASSWORD=secret ./scripts/login_load_test.sh
+set -euo pipefail
+
+BASE_URL="${BASE_URL:-http://127.0.0.1:8080}"
+LOGINS="${LOGINS:-20}"
+PROBES="${PROBES:-10}"
+MAX_PROBE_SECONDS="${MAX_PROBE_SECONDS:-2}"
+: "${EMAIL:?EMAIL must be set}"
+: "${PASSWORD:?PASSWORD must be set}"
+
+login() {
+    curl -s -o /dev/null -w '%{time_total}\n' -X POST "$BASE_URL/api/staffs/login" \
+        -H 'content-type: application/json' \
+        -d "{\"email\":\"$EMAIL\",\"password\":\"$PASSWORD\"}"
+}
+
+# Public endpoint that only does a quick lookup, no password hashing
+probe() {
+    curl -s -o /dev/null -w '%{time_total}\n' -X POST "$BASE_URL/api/staffs/refresh" \
+        -H 'content-type: application/json' \
+        -d '{"refresh_token":"load-test"}'
+}
+
+summary() {
+    sort -n | awk -v name="$1" '
+        { times[NR] = $1; total += $1 }
+        END { printf "%-6s n=%d avg=%.3fs max=%.3fs\n", name, NR, total / NR, times[NR] }'
+}
+
+echo "Idle probe:"
+for _ in $(seq "$PROBES"); do probe; done | summary probe
+
+tmp="$(mktemp)"
+probes="$(mktemp)"
+trap 'rm -f "$tmp" "$probes"' EXIT
+
+echo "Probe during $LOGINS concurrent logins:"
+for _ in $(seq "$LOGINS"); do login >>"$tmp" & done
+sleep 0.2
+for _ in $(seq "$PROBES"); do probe; done >"$probes"
+wait
+summary probe <"$probes"
+summary login <"$tmp"
+
+slowest="$(sort -n "$probes" | tail -n 1)"
+if awk -v slowest="$slowest" -v limit="$MAX_PROBE_SECONDS" 'BEGIN { exit !(slowest > limit) }'; then
+    echo "FAIL: slowest probe took ${slowest}s, limit is ${MAX_PROBE_SECONDS}s" >&2
+    exit 1
+fi
+echo "PASS: slowest probe took ${slowest}s, limit is ${MAX_PROBE_SECONDS}s"
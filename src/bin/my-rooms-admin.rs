@@ -0,0 +1,217 @@
+use chrono::{Duration, Utc};
+use clap::{Parser, Subcommand};
+use diesel::prelude::*;
+use dotenv::dotenv;
+use my_rooms_be::config::app::AppConfig;
+use my_rooms_be::config::database::run_pending_migrations;
+use my_rooms_be::models::room::{CreateOrUpdateRoomTypesRequest, CreateRoomRequest};
+use my_rooms_be::models::staff::{CreateStaffRequest, Role};
+use my_rooms_be::schema::{room_types, staff};
+use my_rooms_be::services::reservation_service::purge_cancelled_reservations;
+use my_rooms_be::services::room_service::{create_room, create_room_type};
+use my_rooms_be::services::staff_service::{
+    create_admin_staff, request_password_reset, reset_staff_password_by_email,
+};
+use my_rooms_be::utils::common::{AppError, DieselResultExt};
+use my_rooms_be::utils::validation::{Validate, ValidationErrors};
+
+// (type name, description, price per night)
+const DEMO_ROOM_TYPES: [(&str, &str, i32); 3] = [
+    ("Standard", "Queen bed, city view", 100),
+    ("Deluxe", "King bed, balcony", 180),
+    ("Suite", "Separate living room, sleeps four", 320),
+];
+
+// (room name, type name, capacity)
+const DEMO_ROOMS: [(&str, &str, i32); 8] = [
+    ("101", "Standard", 2),
+    ("102", "Standard", 2),
+    ("103", "Standard", 2),
+    ("104", "Standard", 2),
+    ("201", "Deluxe", 2),
+    ("202", "Deluxe", 2),
+    ("203", "Deluxe", 2),
+    ("301", "Suite", 4),
+];
+
+/// Maintenance tasks for my-rooms-be, run against the configured database
+#[derive(Parser)]
+#[command(name = "my-rooms-admin")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create an admin staff member, e.g. the very first account
+    CreateAdmin {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        email: String,
+        /// Read from ADMIN_PASSWORD when not given, to keep it out of shell history
+        #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+    /// Set a temporary password for a staff member and lift any login lockout
+    ResetPassword {
+        #[arg(long)]
+        email: String,
+        /// Read from ADMIN_PASSWORD when not given, to keep it out of shell history
+        #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+    /// Issue a one-time password reset token for an active staff member and
+    /// print it, for handing over while no mailer is configured
+    IssueResetToken {
+        #[arg(long)]
+        email: String,
+    },
+    /// Apply pending database migrations
+    Migrate,
+    /// Add demo room types and rooms, skipping any that already exist
+    SeedDemo {
+        /// Staff member the demo data is recorded as created by
+        #[arg(long)]
+        staff_email: String,
+    },
+    /// Delete reservations cancelled more than the given number of days ago
+    PurgeCancelled {
+        #[arg(long, default_value_t = 90)]
+        older_than_days: i64,
+    },
+}
+
+fn main() {
+    dotenv().ok();
+
+    let cli = Cli::parse();
+
+    if let Err(err) = run(cli.command) {
+        eprintln!("{}", describe(&err));
+        std::process::exit(1);
+    }
+}
+
+fn run(command: Command) -> Result<(), AppError> {
+    let database = AppConfig::load_database().map_err(AppError::Internal)?;
+    let mut conn = PgConnection::establish(&database.url)
+        .map_err(|err| AppError::Internal(format!("Failed to connect to database: {}", err)))?;
+
+    match command {
+        Command::CreateAdmin {
+            name,
+            email,
+            password,
+        } => {
+            let request = CreateStaffRequest {
+                name,
+                email,
+                password,
+                position: Role::Admin,
+            };
+            request.validate()?;
+
+            let staff_data =
+                create_admin_staff(&mut conn, &request.name, &request.email, &request.password)?;
+            println!(
+                "Created admin {} with id {}.",
+                staff_data.email, staff_data.id
+            );
+        }
+        Command::ResetPassword { email, password } => {
+            let mut errors = ValidationErrors::new();
+            errors.password("password", &password);
+            errors.into_result()?;
+
+            reset_staff_password_by_email(&mut conn, &email, &password)?;
+            println!(
+                "Password reset for {}, it must be changed on next login.",
+                email
+            );
+        }
+        Command::IssueResetToken { email } => {
+            let token = request_password_reset(&mut conn, &email)?
+                .ok_or_else(|| AppError::NotFound("Active staff not found.".to_string()))?;
+            println!("{}", token);
+        }
+        Command::Migrate => {
+            let versions = run_pending_migrations(&mut conn)?;
+            for version in &versions {
+                println!("Applied {}", version);
+            }
+            println!("{} migration(s) applied.", versions.len());
+        }
+        Command::SeedDemo { staff_email } => seed_demo(&mut conn, &staff_email)?,
+        Command::PurgeCancelled { older_than_days } => {
+            if older_than_days < 0 {
+                return Err(AppError::BadRequest(
+                    "--older-than-days must not be negative.".to_string(),
+                ));
+            }
+
+            let cutoff = Utc::now() - Duration::days(older_than_days);
+            let purged = purge_cancelled_reservations(&mut conn, cutoff)?;
+            println!("Purged {} cancelled reservation(s).", purged);
+        }
+    }
+
+    Ok(())
+}
+
+fn seed_demo(conn: &mut PgConnection, staff_email: &str) -> Result<(), AppError> {
+    let staff_id = staff::table
+        .filter(staff::email.eq(staff_email))
+        .select(staff::id)
+        .first::<i32>(conn)
+        .or_not_found("Staff not found.")?;
+
+    for (type_name, description, price_per_night) in DEMO_ROOM_TYPES {
+        let request = CreateOrUpdateRoomTypesRequest {
+            type_name: type_name.to_string(),
+            description: Some(description.to_string()),
+            price_per_night,
+            room_ids: None,
+        };
+
+        match create_room_type(conn, &request, staff_id) {
+            Ok(()) => println!("Created room type {}.", type_name),
+            Err(AppError::Conflict(_)) => println!("Room type {} exists, skipped.", type_name),
+            Err(err) => return Err(err),
+        }
+    }
+
+    for (room_name, type_name, capacity) in DEMO_ROOMS {
+        let type_id = room_types::table
+            .filter(room_types::type_name.eq(type_name))
+            .select(room_types::id)
+            .first::<i32>(conn)?;
+        let request = CreateRoomRequest {
+            room_name: room_name.to_string(),
+            type_id: Some(type_id),
+            capacity,
+            is_available: true,
+        };
+
+        match create_room(conn, &request, staff_id) {
+            Ok(()) => println!("Created room {}.", room_name),
+            Err(AppError::Conflict(_)) => println!("Room {} exists, skipped.", room_name),
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+// Validation errors carry their details outside of the Display message
+fn describe(err: &AppError) -> String {
+    match err {
+        AppError::Validation(errors) => format!(
+            "{} {}",
+            err,
+            serde_json::to_string(errors).unwrap_or_default()
+        ),
+        _ => err.to_string(),
+    }
+}
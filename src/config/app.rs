@@ -0,0 +1,252 @@
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+// Read when APP_CONFIG does not point somewhere else, skipped if missing
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct AppConfig {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub jwt: JwtConfig,
+    pub cors: CorsConfig,
+    pub log: LogConfig,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    // Reverse proxies whose X-Forwarded-For / Forwarded header names the
+    // client, requests from anywhere else are keyed by their peer address
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub connection_timeout_seconds: u64,
+    // Apply pending embedded migrations on boot, also enabled by --migrate
+    pub run_migrations: bool,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            url: String::new(),
+            max_connections: 10,
+            connection_timeout_seconds: 30,
+            run_migrations: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct JwtConfig {
+    pub secret: String,
+    pub access_token_ttl_minutes: i64,
+    pub refresh_token_ttl_days: i64,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        JwtConfig {
+            secret: String::new(),
+            access_token_ttl_minutes: 15,
+            refresh_token_ttl_days: 30,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LogConfig {
+    pub level: String,
+    // "json" for log shippers, "text" for reading in a terminal
+    pub format: String,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            level: "info".to_string(),
+            format: "json".to_string(),
+        }
+    }
+}
+
+impl AppConfig {
+    // Defaults, then the TOML file, then environment variables, each
+    // overriding the one before.
+    pub fn load() -> Result<Self, String> {
+        let config = Self::load_unchecked()?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    // For tools that only talk to the database and have no use for the rest
+    pub fn load_database() -> Result<DatabaseConfig, String> {
+        let config = Self::load_unchecked()?;
+
+        if config.database.url.is_empty() {
+            return Err(
+                "Invalid configuration: database.url (DATABASE_URL) must be set".to_string(),
+            );
+        }
+
+        Ok(config.database)
+    }
+
+    fn load_unchecked() -> Result<Self, String> {
+        let path = env::var("APP_CONFIG").ok();
+        let mut config = match &path {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(DEFAULT_CONFIG_FILE)?
+            }
+            None => AppConfig::default(),
+        };
+
+        config.apply_env()?;
+
+        Ok(config)
+    }
+
+    fn from_file(path: &str) -> Result<Self, String> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?;
+
+        toml::from_str(&contents).map_err(|err| format!("Failed to parse {}: {}", path, err))
+    }
+
+    fn apply_env(&mut self) -> Result<(), String> {
+        if let Some(host) = env_value("HOST") {
+            self.server.host = host;
+        }
+        if let Some(port) = env_parsed("PORT")? {
+            self.server.port = port;
+        }
+        if let Some(proxies) = env_value("TRUSTED_PROXIES") {
+            self.server.trusted_proxies = proxies
+                .split(',')
+                .map(str::trim)
+                .filter(|proxy| !proxy.is_empty())
+                .map(|proxy| {
+                    proxy
+                        .parse()
+                        .map_err(|_| format!("TRUSTED_PROXIES has an invalid value: {}", proxy))
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(url) = env_value("DATABASE_URL") {
+            self.database.url = url;
+        }
+        if let Some(max_connections) = env_parsed("DATABASE_MAX_CONNECTIONS")? {
+            self.database.max_connections = max_connections;
+        }
+        if let Some(timeout) = env_parsed("DATABASE_CONNECTION_TIMEOUT_SECONDS")? {
+            self.database.connection_timeout_seconds = timeout;
+        }
+        if let Some(run_migrations) = env_parsed("RUN_MIGRATIONS")? {
+            self.database.run_migrations = run_migrations;
+        }
+        if let Some(secret) = env_value("STAFF_JWT_SECRET") {
+            self.jwt.secret = secret;
+        }
+        if let Some(ttl) = env_parsed("ACCESS_TOKEN_TTL_MINUTES")? {
+            self.jwt.access_token_ttl_minutes = ttl;
+        }
+        if let Some(ttl) = env_parsed("REFRESH_TOKEN_TTL_DAYS")? {
+            self.jwt.refresh_token_ttl_days = ttl;
+        }
+        if let Some(origins) = env_value("CORS_ALLOWED_ORIGINS") {
+            self.cors.allowed_origins = origins
+                .split(',')
+                .map(|origin| origin.trim().to_string())
+                .filter(|origin| !origin.is_empty())
+                .collect();
+        }
+        if let Some(level) = env_value("LOG_LEVEL") {
+            self.log.level = level;
+        }
+        if let Some(format) = env_value("LOG_FORMAT") {
+            self.log.format = format;
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+
+        if self.server.port == 0 {
+            problems.push("server.port must not be 0");
+        }
+        if self.database.url.is_empty() {
+            problems.push("database.url (DATABASE_URL) must be set");
+        }
+        if self.database.max_connections == 0 {
+            problems.push("database.max_connections must be greater than 0");
+        }
+        if self.database.connection_timeout_seconds == 0 {
+            problems.push("database.connection_timeout_seconds must be greater than 0");
+        }
+        if self.jwt.secret.is_empty() {
+            problems.push("jwt.secret (STAFF_JWT_SECRET) must be set");
+        }
+        if self.jwt.access_token_ttl_minutes <= 0 {
+            problems.push("jwt.access_token_ttl_minutes must be greater than 0");
+        }
+        if self.jwt.refresh_token_ttl_days <= 0 {
+            problems.push("jwt.refresh_token_ttl_days must be greater than 0");
+        }
+        if !["json", "text"].contains(&self.log.format.as_str()) {
+            problems.push("log.format must be \"json\" or \"text\"");
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Invalid configuration: {}", problems.join(", ")))
+        }
+    }
+}
+
+fn env_value(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|value| !value.is_empty())
+}
+
+fn env_parsed<T: std::str::FromStr>(key: &str) -> Result<Option<T>, String> {
+    env_value(key)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("{} has an invalid value: {}", key, value))
+        })
+        .transpose()
+}
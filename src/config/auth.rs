@@ -1,5 +1,4 @@
-use std::env;
-
-pub fn staff_jwt_secret() -> String {
-    env::var("STAFF_JWT_SECRET").expect("STAFF_JWT_SECRET must be set")
-}
\ No newline at end of file
+pub const PASSWORD_RESET_TTL_MINUTES: i64 = 60;
+pub const MAX_FAILED_LOGIN_ATTEMPTS: i32 = 5;
+pub const MAX_LOGIN_LOCKOUT_MINUTES: i64 = 24 * 60;
+pub const LOGIN_ATTEMPT_WINDOW_MINUTES: i64 = 60;
\ No newline at end of file
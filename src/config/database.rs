@@ -1,14 +1,73 @@
+use crate::config::app::DatabaseConfig;
+use crate::utils::common::AppError;
+use crate::utils::metrics::Metrics;
+use actix_web::web;
 use diesel::pg::PgConnection;
 use diesel::r2d2::{self, ConnectionManager};
-use std::env;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use std::time::Duration;
 
 pub type DbPool = r2d2::Pool<ConnectionManager<PgConnection>>;
 
-pub fn create_connection() -> DbPool {
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let manager = ConnectionManager::<PgConnection>::new(database_url);
-    
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+pub fn create_connection(
+    config: &DatabaseConfig,
+    metrics: &Metrics,
+) -> Result<DbPool, r2d2::PoolError> {
+    let manager = ConnectionManager::<PgConnection>::new(&config.url);
+
     r2d2::Pool::builder()
+        .max_size(config.max_connections)
+        .connection_timeout(Duration::from_secs(config.connection_timeout_seconds))
+        .event_handler(Box::new(metrics.pool_event_handler()))
         .build(manager)
-        .expect("Failed to create pool.")
+}
+
+// Diesel and bcrypt block the thread they run on, so handlers hand that work
+// to actix's blocking thread pool instead of stalling the async workers.
+pub async fn with_connection<F, T>(pool: &DbPool, f: F) -> Result<T, AppError>
+where
+    F: FnOnce(&mut PgConnection) -> Result<T, AppError> + Send + 'static,
+    T: Send + 'static,
+{
+    let pool = pool.clone();
+
+    web::block(move || {
+        let mut conn = pool.get()?;
+        f(&mut conn)
+    })
+    .await?
+}
+
+pub fn has_pending_migrations(conn: &mut PgConnection) -> Result<bool, AppError> {
+    conn.has_pending_migration(MIGRATIONS)
+        .map_err(|_| AppError::Internal("Failed to check migrations.".to_string()))
+}
+
+// Returns the versions that were applied
+pub fn run_pending_migrations(conn: &mut PgConnection) -> Result<Vec<String>, AppError> {
+    conn.run_pending_migrations(MIGRATIONS)
+        .map(|versions| versions.iter().map(ToString::to_string).collect())
+        .map_err(|err| AppError::Internal(format!("Failed to run migrations: {}", err)))
+}
+
+// Run before serving, the queries in schema.rs only work against a database
+// that has every embedded migration applied.
+pub fn prepare_database(pool: &DbPool, run_migrations: bool) -> Result<(), AppError> {
+    let mut conn = pool.get()?;
+
+    if run_migrations {
+        for version in run_pending_migrations(&mut conn)? {
+            tracing::info!(%version, "applied migration");
+        }
+    }
+
+    if has_pending_migrations(&mut conn)? {
+        return Err(AppError::Internal(
+            "Database has pending migrations, start with --migrate to apply them.".to_string(),
+        ));
+    }
+
+    Ok(())
 }
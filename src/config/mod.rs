@@ -1,2 +1,3 @@
 pub mod database;
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod app;
\ No newline at end of file
@@ -0,0 +1,25 @@
+use crate::config::database::{with_connection, DbPool};
+use crate::models::audit_event::AuditSearchParams;
+use crate::services::audit_service::get_audit_events_with_pagination;
+use crate::utils::common::AppError;
+use crate::utils::response::StandardResponse;
+use crate::utils::validation::pagination;
+use actix_web::{web, HttpResponse};
+
+pub async fn get_audit_events_with_pagination_handler(
+    pool: web::Data<DbPool>,
+    params: web::Query<AuditSearchParams>,
+) -> Result<HttpResponse, AppError> {
+    let (page, page_size) = pagination(params.page, params.page_size)?;
+
+    let (data, meta) = with_connection(&pool, move |conn| {
+        get_audit_events_with_pagination(conn, &params, page, page_size)
+    })
+    .await?;
+
+    Ok(
+        HttpResponse::Ok().json(StandardResponse::success_with_pagination(
+            data, "success", meta,
+        )),
+    )
+}
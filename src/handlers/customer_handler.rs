@@ -0,0 +1,86 @@
+use crate::config::database::{with_connection, DbPool};
+use crate::models::customer_contact::{
+    CreateOrUpdateCustomerRequest, CustomerSearchParams, MergeCustomersRequest,
+};
+use crate::services::customer_service::{
+    create_customer, get_customer_by_id, get_customers_with_pagination, merge_customers,
+    update_customer_by_id,
+};
+use crate::utils::common::AppError;
+use crate::utils::response::StandardResponse;
+use crate::utils::validation::{pagination, Validate};
+use actix_web::{web, HttpResponse};
+
+pub async fn get_customers_with_pagination_handler(
+    pool: web::Data<DbPool>,
+    params: web::Query<CustomerSearchParams>,
+) -> Result<HttpResponse, AppError> {
+    let (page, page_size) = pagination(params.page, params.page_size)?;
+
+    let (data, meta) = with_connection(&pool, move |conn| {
+        get_customers_with_pagination(conn, params.search.as_deref(), page, page_size)
+    })
+    .await?;
+
+    Ok(
+        HttpResponse::Ok().json(StandardResponse::success_with_pagination(
+            data, "success", meta,
+        )),
+    )
+}
+
+pub async fn get_customer_by_id_handler(
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+
+    let data = with_connection(&pool, move |conn| get_customer_by_id(conn, id)).await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::success_with_data(data, "success")))
+}
+
+pub async fn create_customer_handler(
+    pool: web::Data<DbPool>,
+    body: web::Json<CreateOrUpdateCustomerRequest>,
+) -> Result<HttpResponse, AppError> {
+    body.validate()?;
+
+    with_connection(&pool, move |conn| create_customer(conn, &body)).await?;
+
+    Ok(
+        HttpResponse::Created().json(StandardResponse::<()>::success(
+            "Customer created successfully.",
+        )),
+    )
+}
+
+pub async fn update_customer_by_id_handler(
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+    body: web::Json<CreateOrUpdateCustomerRequest>,
+) -> Result<HttpResponse, AppError> {
+    body.validate()?;
+
+    let id = path.into_inner();
+
+    with_connection(&pool, move |conn| update_customer_by_id(conn, id, &body)).await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::<()>::success(
+        "Customer updated successfully.",
+    )))
+}
+
+pub async fn merge_customers_handler(
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+    body: web::Json<MergeCustomersRequest>,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+
+    with_connection(&pool, move |conn| merge_customers(conn, id, &body)).await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::<()>::success(
+        "Customers merged successfully.",
+    )))
+}
@@ -0,0 +1,36 @@
+use crate::config::database::{has_pending_migrations, with_connection, DbPool};
+use crate::utils::common::AppError;
+use crate::utils::response::StandardResponse;
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+
+// Liveness, answers as long as the server can handle requests at all
+pub async fn health_handler() -> HttpResponse {
+    HttpResponse::Ok().json(StandardResponse::<()>::success("OK"))
+}
+
+// Readiness, only once Postgres is reachable and fully migrated
+pub async fn ready_handler(pool: web::Data<DbPool>) -> Result<HttpResponse, AppError> {
+    let pending = with_connection(&pool, has_pending_migrations)
+        .await
+        .map_err(|_| AppError::ServiceUnavailable("Database is unreachable.".to_string()))?;
+
+    if pending {
+        return Err(AppError::ServiceUnavailable(
+            "Database migrations are pending.".to_string(),
+        ));
+    }
+
+    Ok(HttpResponse::Ok().json(StandardResponse::<()>::success("Ready")))
+}
+
+pub async fn version_handler() -> HttpResponse {
+    HttpResponse::Ok().json(StandardResponse::success_with_data(
+        json!({
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "git_sha": env!("GIT_SHA"),
+        }),
+        "success",
+    ))
+}
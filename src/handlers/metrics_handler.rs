@@ -0,0 +1,20 @@
+use crate::config::database::{with_connection, DbPool};
+use crate::services::reservation_service::get_room_occupancy;
+use crate::utils::common::AppError;
+use crate::utils::metrics::Metrics;
+use actix_web::{web, HttpResponse};
+
+pub async fn metrics_handler(
+    pool: web::Data<DbPool>,
+    metrics: web::Data<Metrics>,
+) -> Result<HttpResponse, AppError> {
+    metrics.record_pool_state(&pool);
+
+    let (total_rooms, occupied_rooms) = with_connection(&pool, get_room_occupancy).await?;
+    metrics.rooms_total.set(total_rooms);
+    metrics.rooms_occupied.set(occupied_rooms);
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(metrics.render()))
+}
@@ -1,3 +1,7 @@
 pub mod room_handler;
 pub mod staff_handler;
-pub mod reservation_handler;
\ No newline at end of file
+pub mod reservation_handler;
+pub mod customer_handler;
+pub mod audit_handler;
+pub mod health_handler;
+pub mod metrics_handler;
\ No newline at end of file
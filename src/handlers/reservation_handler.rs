@@ -1,45 +1,46 @@
-use crate::config::database::DbPool;
-use crate::models::reservation::CreateOrUpdateReservationRequest;
+use crate::config::database::{with_connection, DbPool};
+use crate::models::reservation::{
+    CalendarParams, CancelReservationRequest, CreateOrUpdateReservationRequest, ReservationStatus,
+};
 use crate::services::reservation_service::{
-    create_reservation, get_reservations_with_pagination, update_reservation_by_id,
+    create_reservation, get_reservation_by_id, get_reservation_calendar,
+    get_reservations_with_pagination, update_reservation_by_id, update_reservation_status_by_id,
 };
 use crate::utils::common::{AppError, PaginationParams};
+use crate::utils::metrics::Metrics;
 use crate::utils::response::StandardResponse;
+use crate::utils::validation::{pagination, Validate};
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
-use diesel::r2d2::ConnectionManager;
-use diesel::PgConnection;
-use r2d2::PooledConnection;
 
 pub async fn create_reservation_handler(
     pool: web::Data<DbPool>,
+    metrics: web::Data<Metrics>,
     req: HttpRequest,
     body: web::Json<CreateOrUpdateReservationRequest>,
-) -> HttpResponse {
-    let mut conn: PooledConnection<ConnectionManager<PgConnection>> = match pool.get() {
-        Ok(connection) => connection,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(StandardResponse::<()>::error(
-                "Failed to get DB connection.",
-            ))
-        }
-    };
-
-    let staff_id = req.extensions().get::<i32>().unwrap().clone();
-
-    match create_reservation(&mut conn, &body, staff_id) {
-        Ok(_) => HttpResponse::Created().json(StandardResponse::<()>::success(
+) -> Result<HttpResponse, AppError> {
+    body.validate()?;
+
+    let staff_id = *req.extensions().get::<i32>().unwrap();
+
+    with_connection(&pool, move |conn| create_reservation(conn, &body, staff_id)).await?;
+    metrics.reservations_created_total.inc();
+
+    Ok(
+        HttpResponse::Created().json(StandardResponse::<()>::success(
             "Reservation created successfully.",
         )),
-        Err(AppError::BadRequest(msg)) => {
-            return HttpResponse::BadRequest().json(StandardResponse::<()>::error(&msg))
-        }
-        Err(err) => {
-            println!("Error: {:#?}", err);
-            return HttpResponse::BadRequest().json(StandardResponse::<()>::error(
-                "Failed to create reservation.",
-            ));
-        }
-    }
+    )
+}
+
+pub async fn get_reservation_by_id_handler(
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+
+    let data = with_connection(&pool, move |conn| get_reservation_by_id(conn, id)).await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::success_with_data(data, "success")))
 }
 
 pub async fn update_reservation_by_id_handler(
@@ -47,58 +48,132 @@ pub async fn update_reservation_by_id_handler(
     pool: web::Data<DbPool>,
     req: HttpRequest,
     body: web::Json<CreateOrUpdateReservationRequest>,
-) -> HttpResponse {
-    let mut conn: PooledConnection<ConnectionManager<PgConnection>> = match pool.get() {
-        Ok(connection) => connection,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(StandardResponse::<()>::error(
-                "Failed to get DB connection.",
-            ))
-        }
-    };
+) -> Result<HttpResponse, AppError> {
+    body.validate()?;
 
     let id = path.into_inner();
-    let staff_id = req.extensions().get::<i32>().unwrap().clone();
+    let staff_id = *req.extensions().get::<i32>().unwrap();
+
+    with_connection(&pool, move |conn| {
+        update_reservation_by_id(conn, id, &body, staff_id)
+    })
+    .await?;
 
-    match update_reservation_by_id(&mut conn, id, &body, staff_id) {
-        Ok(_) => HttpResponse::Created().json(StandardResponse::<()>::success(
+    Ok(
+        HttpResponse::Created().json(StandardResponse::<()>::success(
             "Reservation updated successfully.",
         )),
-        Err(AppError::BadRequest(msg)) => {
-            return HttpResponse::BadRequest().json(StandardResponse::<()>::error(&msg))
-        }
-        Err(AppError::DatabaseError(diesel::result::Error::NotFound)) => {
-            HttpResponse::NotFound().json(StandardResponse::<()>::error("Reservation not found."))
-        }
-        Err(err) => {
-            println!("Error: {:#?}", err);
-            return HttpResponse::BadRequest().json(StandardResponse::<()>::error(
-                "Failed to updated reservation.",
-            ));
-        }
-    }
+    )
 }
 
 pub async fn get_reservations_with_pagination_handler(
     pool: web::Data<DbPool>,
     params: web::Query<PaginationParams>,
-) -> HttpResponse {
-    let mut conn: PooledConnection<ConnectionManager<PgConnection>> = match pool.get() {
-        Ok(connection) => connection,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(StandardResponse::<()>::error(
-                "Failed to get DB connection.",
-            ))
-        }
-    };
-    let page = params.page.unwrap_or(1);
-    let page_size = params.page_size.unwrap_or(10);
-
-    match get_reservations_with_pagination(&mut conn, page, page_size) {
-        Ok((data, meta)) => HttpResponse::Ok().json(StandardResponse::success_with_pagination(
+) -> Result<HttpResponse, AppError> {
+    let (page, page_size) = pagination(params.page, params.page_size)?;
+
+    let (data, meta) = with_connection(&pool, move |conn| {
+        get_reservations_with_pagination(conn, page, page_size)
+    })
+    .await?;
+
+    Ok(
+        HttpResponse::Ok().json(StandardResponse::success_with_pagination(
             data, "success", meta,
         )),
-        Err(_) => HttpResponse::InternalServerError()
-            .json(StandardResponse::<()>::error("Failed to get reservations.")),
-    }
+    )
+}
+
+pub async fn get_reservation_calendar_handler(
+    pool: web::Data<DbPool>,
+    params: web::Query<CalendarParams>,
+) -> Result<HttpResponse, AppError> {
+    let data = with_connection(&pool, move |conn| get_reservation_calendar(conn, &params)).await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::success_with_data(data, "success")))
+}
+
+async fn change_reservation_status(
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+    reservation_id: i32,
+    status: ReservationStatus,
+    cancellation_reason: Option<String>,
+    success_message: &str,
+) -> Result<HttpResponse, AppError> {
+    let staff_id = *req.extensions().get::<i32>().unwrap();
+
+    with_connection(&pool, move |conn| {
+        update_reservation_status_by_id(conn, reservation_id, status, cancellation_reason, staff_id)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::<()>::success(success_message)))
+}
+
+pub async fn confirm_reservation_handler(
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    change_reservation_status(
+        pool,
+        req,
+        path.into_inner(),
+        ReservationStatus::Confirmed,
+        None,
+        "Reservation confirmed successfully.",
+    )
+    .await
+}
+
+pub async fn cancel_reservation_handler(
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+    body: web::Json<CancelReservationRequest>,
+) -> Result<HttpResponse, AppError> {
+    body.validate()?;
+
+    change_reservation_status(
+        pool,
+        req,
+        path.into_inner(),
+        ReservationStatus::Cancelled,
+        Some(body.into_inner().reason),
+        "Reservation cancelled successfully.",
+    )
+    .await
+}
+
+pub async fn check_in_reservation_handler(
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    change_reservation_status(
+        pool,
+        req,
+        path.into_inner(),
+        ReservationStatus::CheckedIn,
+        None,
+        "Reservation checked in successfully.",
+    )
+    .await
+}
+
+pub async fn check_out_reservation_handler(
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    change_reservation_status(
+        pool,
+        req,
+        path.into_inner(),
+        ReservationStatus::CheckedOut,
+        None,
+        "Reservation checked out successfully.",
+    )
+    .await
 }
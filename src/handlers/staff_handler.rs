@@ -1,87 +1,254 @@
-use crate::config::database::DbPool;
-use crate::models::staff::{CreateStaffRequest, LoginRequest, UpdateStaffRequest};
-use crate::services::staff_service::{authenticate_staff, create_staff, update_staff_by_id};
+use crate::config::app::AppConfig;
+use crate::config::database::{with_connection, DbPool};
+use crate::models::jwt::Claims;
+use crate::models::staff::{
+    ChangePasswordRequest, CreateStaffRequest, LoginRequest, Role, UpdateStaffRequest,
+};
+use crate::models::staff_password_reset::{
+    ConfirmPasswordResetRequest, RequestPasswordResetRequest, VerifyPasswordResetRequest,
+};
+use crate::models::staff_session::RefreshTokenRequest;
+use crate::services::staff_service::{
+    change_own_password, confirm_password_reset, create_staff, finish_login, get_staff_by_id,
+    refresh_session, request_password_reset, revoke_session, start_login, unlock_staff_by_id,
+    update_staff_by_id, verify_password_reset,
+};
+use crate::utils::common::AppError;
+use crate::utils::mailer::Mailer;
+use crate::utils::metrics::Metrics;
 use crate::utils::response::StandardResponse;
-use actix_web::{web, HttpResponse};
-use diesel::r2d2::ConnectionManager;
-use diesel::PgConnection;
-use r2d2::PooledConnection;
+use crate::utils::validation::Validate;
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
 
 pub async fn create_staff_handler(
     pool: web::Data<DbPool>,
+    http_req: HttpRequest,
     req: web::Json<CreateStaffRequest>,
-) -> HttpResponse {
-    let mut conn: PooledConnection<ConnectionManager<PgConnection>> = match pool.get() {
-        Ok(connection) => connection,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(StandardResponse::<()>::error(
-                "Failed to get DB connection.",
-            ))
-        }
-    };
+) -> Result<HttpResponse, AppError> {
+    req.validate()?;
+
+    let staff_id = *http_req.extensions().get::<i32>().unwrap();
+    let role = *http_req.extensions().get::<Role>().unwrap();
+
+    with_connection(&pool, move |conn| create_staff(conn, &req, staff_id, role)).await?;
 
-    match create_staff(&mut conn, &req) {
-        Ok(_) => HttpResponse::Created().json(StandardResponse::<()>::success(
+    Ok(
+        HttpResponse::Created().json(StandardResponse::<()>::success(
             "Staff created successfully.",
         )),
-        Err(diesel::result::Error::DatabaseError(
-            diesel::result::DatabaseErrorKind::UniqueViolation,
-            _,
-        )) => HttpResponse::Conflict()
-            .json(StandardResponse::<()>::error("Staff email already exists.")),
-        Err(_) => HttpResponse::BadRequest()
-            .json(StandardResponse::<()>::error("Failed to create staff.")),
-    }
+    )
+}
+
+pub async fn get_staff_by_id_handler(
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+
+    let data = with_connection(&pool, move |conn| get_staff_by_id(conn, id)).await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::success_with_data(data, "success")))
 }
 
 pub async fn update_staff_by_id_handler(
     path: web::Path<i32>,
     pool: web::Data<DbPool>,
+    http_req: HttpRequest,
     mut req: web::Json<UpdateStaffRequest>,
-) -> HttpResponse {
-    let mut conn: PooledConnection<ConnectionManager<PgConnection>> = match pool.get() {
-        Ok(connection) => connection,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(StandardResponse::<()>::error(
-                "Failed to get DB connection.",
-            ))
-        }
-    };
+) -> Result<HttpResponse, AppError> {
+    req.validate()?;
+
     let id = path.into_inner();
+    let staff_id = *http_req.extensions().get::<i32>().unwrap();
+    let role = *http_req.extensions().get::<Role>().unwrap();
 
-    match update_staff_by_id(&mut conn, id, &mut req) {
-        Ok(_) => HttpResponse::Ok().json(StandardResponse::<()>::success(
-            "Staff updated successfully.",
-        )),
-        Err(diesel::result::Error::NotFound) => {
-            HttpResponse::NotFound().json(StandardResponse::<()>::error("Staff not found."))
-        }
-        Err(_) => HttpResponse::InternalServerError()
-            .json(StandardResponse::<()>::error("Failed to update staff.")),
-    }
+    with_connection(&pool, move |conn| {
+        update_staff_by_id(conn, id, &mut req, staff_id, role)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::<()>::success(
+        "Staff updated successfully.",
+    )))
 }
 
 pub async fn login_staff_handler(
     pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    metrics: web::Data<Metrics>,
+    http_req: HttpRequest,
     req: web::Json<LoginRequest>,
-) -> HttpResponse {
-    let mut conn: PooledConnection<ConnectionManager<PgConnection>> = match pool.get() {
-        Ok(connection) => connection,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json(StandardResponse::<()>::error(
-                "Failed to get DB connection.",
-            ))
-        }
+) -> Result<HttpResponse, AppError> {
+    let ip_address = client_ip(&http_req, &config.server.trusted_proxies);
+    let jwt_config = config.jwt.clone();
+    let LoginRequest { email, password } = req.into_inner();
+
+    // The password check runs between two short connection checkouts, so
+    // logins in flight cannot drain the pool
+    let result = async {
+        let lookup_ip = ip_address.clone();
+        let attempt = with_connection(&pool, move |conn| {
+            start_login(conn, &email, lookup_ip.as_deref())
+        })
+        .await?;
+
+        let attempt = web::block(move || attempt.check_password(&password)).await?;
+
+        with_connection(&pool, move |conn| {
+            finish_login(conn, attempt, ip_address.as_deref(), &jwt_config)
+        })
+        .await
+    }
+    .await;
+
+    let outcome = match &result {
+        Ok(_) => "success",
+        Err(AppError::Unauthorized(_)) => "failure",
+        Err(AppError::TooManyRequests(_)) => "locked",
+        Err(_) => "error",
+    };
+    metrics
+        .login_attempts_total
+        .with_label_values(&[outcome])
+        .inc();
+
+    let (staff_data, tokens) = result?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::success_with_data(
+        json!({
+            "id": staff_data.id,
+            "name": staff_data.name,
+            "must_change_password": staff_data.must_change_password,
+            "token": tokens.token,
+            "refresh_token": tokens.refresh_token,
+        }),
+        "Success",
+    )))
+}
+
+// Address of the client behind a trusted proxy, the peer address otherwise.
+// None when it cannot be told.
+fn client_ip(http_req: &HttpRequest, trusted_proxies: &[IpAddr]) -> Option<String> {
+    let peer_ip = http_req.peer_addr()?.ip();
+
+    if !trusted_proxies.contains(&peer_ip) {
+        return Some(peer_ip.to_string());
+    }
+
+    let connection_info = http_req.connection_info();
+    let real_ip = connection_info.realip_remote_addr()?;
+
+    // The header may carry a port along with the address
+    real_ip
+        .parse::<IpAddr>()
+        .or_else(|_| real_ip.parse::<SocketAddr>().map(|addr| addr.ip()))
+        .ok()
+        .map(|ip| ip.to_string())
+}
+
+pub async fn refresh_staff_token_handler(
+    pool: web::Data<DbPool>,
+    config: web::Data<AppConfig>,
+    req: web::Json<RefreshTokenRequest>,
+) -> Result<HttpResponse, AppError> {
+    let jwt_config = config.jwt.clone();
+
+    let tokens = with_connection(&pool, move |conn| {
+        refresh_session(conn, &req.refresh_token, &jwt_config)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::success_with_data(tokens, "Success")))
+}
+
+pub async fn logout_staff_handler(
+    pool: web::Data<DbPool>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let session_id = http_req.extensions().get::<Claims>().unwrap().sid;
+
+    with_connection(&pool, move |conn| revoke_session(conn, session_id)).await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::<()>::success("Logged out successfully.")))
+}
+
+pub async fn change_own_password_handler(
+    pool: web::Data<DbPool>,
+    http_req: HttpRequest,
+    req: web::Json<ChangePasswordRequest>,
+) -> Result<HttpResponse, AppError> {
+    req.validate()?;
+
+    let claims = http_req.extensions().get::<Claims>().unwrap().clone();
+
+    with_connection(&pool, move |conn| {
+        change_own_password(conn, claims.sub, claims.sid, &req)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::<()>::success(
+        "Password changed successfully.",
+    )))
+}
+
+pub async fn request_password_reset_handler(
+    pool: web::Data<DbPool>,
+    mailer: web::Data<dyn Mailer>,
+    req: web::Json<RequestPasswordResetRequest>,
+) -> Result<HttpResponse, AppError> {
+    let email = req.into_inner().email;
+    let token = {
+        let email = email.clone();
+        with_connection(&pool, move |conn| request_password_reset(conn, &email)).await?
     };
 
-    match authenticate_staff(&mut conn, &req.email, &req.password) {
-        Ok((staff_id, name, token)) => {
-            HttpResponse::Ok().json(StandardResponse::success_with_data(
-                json!({"id": staff_id, "name": name, "token": token }),
-                "Success",
-            ))
-        }
-        Err(msg) => HttpResponse::Unauthorized().json(StandardResponse::<()>::error(&msg)),
+    if let Some(token) = token {
+        mailer.send_password_reset(&email, &token)?;
     }
+
+    Ok(HttpResponse::Ok().json(StandardResponse::<()>::success(
+        "If the email belongs to an active account, a reset token has been issued.",
+    )))
+}
+
+pub async fn verify_password_reset_handler(
+    pool: web::Data<DbPool>,
+    req: web::Json<VerifyPasswordResetRequest>,
+) -> Result<HttpResponse, AppError> {
+    with_connection(&pool, move |conn| verify_password_reset(conn, &req.token)).await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::<()>::success("Reset token is valid.")))
+}
+
+pub async fn confirm_password_reset_handler(
+    pool: web::Data<DbPool>,
+    req: web::Json<ConfirmPasswordResetRequest>,
+) -> Result<HttpResponse, AppError> {
+    req.validate()?;
+
+    with_connection(&pool, move |conn| {
+        confirm_password_reset(conn, &req.token, &req.new_password)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::<()>::success(
+        "Password reset successfully.",
+    )))
+}
+
+pub async fn unlock_staff_by_id_handler(
+    path: web::Path<i32>,
+    pool: web::Data<DbPool>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+    let staff_id = *http_req.extensions().get::<i32>().unwrap();
+
+    with_connection(&pool, move |conn| unlock_staff_by_id(conn, id, staff_id)).await?;
+
+    Ok(HttpResponse::Ok().json(StandardResponse::<()>::success(
+        "Staff unlocked successfully.",
+    )))
 }
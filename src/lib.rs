@@ -0,0 +1,8 @@
+pub mod config;
+pub mod handlers;
+pub mod middlewares;
+pub mod models;
+pub mod routes;
+pub mod schema;
+pub mod services;
+pub mod utils;
@@ -1,28 +1,89 @@
-mod config;
-mod handlers;
-mod middlewares;
-mod models;
-mod routes;
-mod schema;
-mod services;
-mod utils;
-
+use actix_cors::Cors;
 use actix_web::{web, App, HttpServer};
-use config::database::create_connection;
 use dotenv::dotenv;
+use my_rooms_be::config::app::AppConfig;
+use my_rooms_be::config::database::{create_connection, prepare_database};
+use my_rooms_be::middlewares::metrics::RequestMetrics;
+use my_rooms_be::middlewares::request_id::RequestTracing;
+use my_rooms_be::routes;
+use my_rooms_be::utils::common::AppError;
+use my_rooms_be::utils::mailer::{Mailer, NoopMailer};
+use my_rooms_be::utils::metrics::Metrics;
+use my_rooms_be::utils::validation::ValidationErrors;
+use std::sync::Arc;
+use tracing_subscriber::EnvFilter;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
 
-    let db_pool = create_connection();
+    let config = AppConfig::load().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    });
+
+    // RUST_LOG, when set, takes precedence over the configured level
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.log.level));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    if config.log.format == "json" {
+        subscriber
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .init();
+    } else {
+        subscriber.init();
+    }
+
+    let metrics = web::Data::new(Metrics::new());
+    let mailer: web::Data<dyn Mailer> = web::Data::from(Arc::new(NoopMailer) as Arc<dyn Mailer>);
+
+    let db_pool = create_connection(&config.database, &metrics).unwrap_or_else(|err| {
+        tracing::error!(error = %err, "failed to create pool");
+        std::process::exit(1);
+    });
+
+    let run_migrations =
+        config.database.run_migrations || std::env::args().any(|arg| arg == "--migrate");
+    if let Err(err) = prepare_database(&db_pool, run_migrations) {
+        tracing::error!(error = %err, "database is not ready");
+        std::process::exit(1);
+    }
+
+    let bind_address = (config.server.host.clone(), config.server.port);
+    tracing::info!(host = %bind_address.0, port = bind_address.1, "listening");
 
     HttpServer::new(move || {
+        let cors = config
+            .cors
+            .allowed_origins
+            .iter()
+            .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+            .allow_any_method()
+            .allow_any_header()
+            .max_age(3600);
+
         App::new()
+            .wrap(cors)
+            .wrap(RequestMetrics)
+            .wrap(RequestTracing)
+            .app_data(web::Data::new(config.clone()))
+            .app_data(metrics.clone())
+            .app_data(mailer.clone())
             .app_data(web::Data::new(db_pool.clone()))
+            .app_data(web::JsonConfig::default().error_handler(|err, _| {
+                AppError::Validation(ValidationErrors::single("body", &err.to_string())).into()
+            }))
+            .app_data(web::QueryConfig::default().error_handler(|err, _| {
+                AppError::Validation(ValidationErrors::single("query", &err.to_string())).into()
+            }))
+            .app_data(web::PathConfig::default().error_handler(|err, _| {
+                AppError::Validation(ValidationErrors::single("path", &err.to_string())).into()
+            }))
             .configure(routes::routes::init_routes)
     })
-    .bind(("127.0.0.1", 8080))?
+    .bind(bind_address)?
     .run()
     .await
 }
@@ -1,18 +1,31 @@
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
-use actix_web::error::ErrorUnauthorized;
-use actix_web::{Error, HttpMessage};
+use actix_web::{web, Error, HttpMessage};
 use futures_util::future::LocalBoxFuture;
 use jsonwebtoken::{decode, DecodingKey, Validation};
+use std::rc::Rc;
 
+use crate::config::app::AppConfig;
+use crate::config::database::{with_connection, DbPool};
 use crate::models::jwt::Claims;
+use crate::services::staff_service::find_active_session;
+use crate::utils::common::AppError;
 
+// Tokens are checked against the secret in the shared AppConfig
+#[derive(Default)]
 pub struct JwtMiddleware {
-    secret: String,
+    allow_password_change_pending: bool,
 }
 
 impl JwtMiddleware {
-    pub fn new(secret: String) -> Self {
-        JwtMiddleware { secret }
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Lets staff who still have to change their password through, only for
+    // the routes they need to do so.
+    pub fn allow_password_change_pending(mut self) -> Self {
+        self.allow_password_change_pending = true;
+        self
     }
 }
 
@@ -30,14 +43,19 @@ where
     type Future = LocalBoxFuture<'static, Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        let secret = self.secret.clone();
-        Box::pin(async move { Ok(JwtMiddlewareMiddleware { service, secret }) })
+        let allow_password_change_pending = self.allow_password_change_pending;
+        Box::pin(async move {
+            Ok(JwtMiddlewareMiddleware {
+                service: Rc::new(service),
+                allow_password_change_pending,
+            })
+        })
     }
 }
 
 pub struct JwtMiddlewareMiddleware<S> {
-    service: S,
-    secret: String,
+    service: Rc<S>,
+    allow_password_change_pending: bool,
 }
 
 impl<S, B> Service<ServiceRequest> for JwtMiddlewareMiddleware<S>
@@ -58,37 +76,56 @@ where
     }
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let secret = self.secret.clone();
-        let auth_header = req.headers().get("Authorization").cloned();
-        let mut is_auth = false;
+        let service = Rc::clone(&self.service);
+        let allow_password_change_pending = self.allow_password_change_pending;
+        let pool = req.app_data::<web::Data<DbPool>>().cloned();
+        let config = req.app_data::<web::Data<AppConfig>>().cloned();
+        let mut claims = None;
 
         // Validate token
-        if let Some(auth_header) = auth_header {
+        if let (Some(auth_header), Some(config)) = (req.headers().get("Authorization"), config) {
             if let Ok(auth_str) = auth_header.to_str() {
                 // Expecting "Bearer <token>"
-                if auth_str.starts_with("Bearer ") {
-                    let token = &auth_str[7..]; // Strip "Bearer " prefix
-
+                if let Some(token) = auth_str.strip_prefix("Bearer ") {
                     let validation = Validation::default();
-                    let decoding_key = DecodingKey::from_secret(secret.as_ref());
+                    let decoding_key = DecodingKey::from_secret(config.jwt.secret.as_ref());
 
-                    // Validate the token
-                    if let Ok(token_data) = decode::<Claims>(token, &decoding_key, &validation) {
-                        req.request().extensions_mut().insert(token_data.claims.sub);
-                        is_auth = true;
-                    }
+                    claims = decode::<Claims>(token, &decoding_key, &validation)
+                        .ok()
+                        .map(|token_data| token_data.claims);
                 }
             }
         }
 
-        let fut = self.service.call(req);
-
         Box::pin(async move {
-            if is_auth {
-                return fut.await;
+            let unauthorized = || AppError::Unauthorized("Invalid or missing JWT.".to_string());
+            let (Some(claims), Some(pool)) = (claims, pool) else {
+                return Err(unauthorized().into());
+            };
+
+            // Check that the token's session has not been revoked. A failed
+            // lookup is a server error, the token itself may be fine.
+            let (session_id, staff_id) = (claims.sid, claims.sub);
+            let must_change_password = with_connection(&pool, move |conn| {
+                find_active_session(conn, session_id, staff_id)
+            })
+            .await?
+            .ok_or_else(unauthorized)?;
+
+            if must_change_password && !allow_password_change_pending {
+                return Err(AppError::Forbidden("Password change required.".to_string()).into());
+            }
+
+            tracing::Span::current().record("staff_id", claims.sub);
+
+            {
+                let mut extensions = req.request().extensions_mut();
+                extensions.insert(claims.sub);
+                extensions.insert(claims.role);
+                extensions.insert(claims);
             }
 
-            Err(ErrorUnauthorized("Invalid or missing JWT"))
+            service.call(req).await
         })
     }
 }
@@ -0,0 +1,84 @@
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{web, Error};
+use futures_util::future::LocalBoxFuture;
+use std::time::Instant;
+
+use crate::utils::metrics::Metrics;
+
+// Counts and times every request by its route pattern, e.g. `/api/rooms/{id}`,
+// so ids in paths do not each become their own series.
+pub struct RequestMetrics;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestMetricsMiddleware<S>;
+    type Future = LocalBoxFuture<'static, Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        Box::pin(async move { Ok(RequestMetricsMiddleware { service }) })
+    }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let metrics = req.app_data::<web::Data<Metrics>>().cloned();
+        let method = req.method().to_string();
+        // Resolved up front as errors from inner middlewares drop the request
+        let route = req
+            .resource_map()
+            .match_pattern(req.path())
+            .unwrap_or_else(|| "unmatched".to_string());
+        let started_at = Instant::now();
+
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let result = fut.await;
+
+            if let Some(metrics) = metrics {
+                let status = match &result {
+                    Ok(res) => res.status(),
+                    Err(err) => err.as_response_error().status_code(),
+                };
+
+                metrics
+                    .http_requests_total
+                    .with_label_values(&[&method, &route, status.as_str()])
+                    .inc();
+                metrics
+                    .http_request_duration_seconds
+                    .with_label_values(&[&method, &route])
+                    .observe(started_at.elapsed().as_secs_f64());
+            }
+
+            result
+        })
+    }
+}
@@ -1 +1,4 @@
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod role;
+pub mod metrics;
+pub mod request_id;
\ No newline at end of file
@@ -0,0 +1,142 @@
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::InternalError;
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use actix_web::Error;
+use futures_util::future::LocalBoxFuture;
+use rand::RngCore;
+use std::time::Instant;
+use tracing::{field, Instrument};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+// Id of the request being handled, for error bodies built away from the request
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+// Tags every request with an id, taken from the caller's X-Request-Id when it
+// looks sane and generated otherwise, and runs it inside a tracing span. The
+// id is echoed back in the X-Request-Id response header and in error bodies.
+pub struct RequestTracing;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTracing
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestTracingMiddleware<S>;
+    type Future = LocalBoxFuture<'static, Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        Box::pin(async move { Ok(RequestTracingMiddleware { service }) })
+    }
+}
+
+pub struct RequestTracingMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTracingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| is_valid_request_id(value))
+            .map(str::to_string)
+            .unwrap_or_else(generate_request_id);
+        let route = req
+            .resource_map()
+            .match_pattern(req.path())
+            .unwrap_or_else(|| "unmatched".to_string());
+
+        // staff_id is filled in by JwtMiddleware once the caller is known
+        let span = tracing::info_span!(
+            "request",
+            request_id = %request_id,
+            method = %req.method(),
+            route = %route,
+            staff_id = field::Empty,
+        );
+
+        let fut = span.in_scope(|| self.service.call(req));
+        let started_at = Instant::now();
+
+        Box::pin(
+            CURRENT_REQUEST_ID
+                .scope(request_id.clone(), async move {
+                    let result: Result<Self::Response, Error> = match fut.await {
+                        Ok(mut res) => {
+                            set_request_id_header(res.headers_mut(), &request_id);
+                            Ok(res)
+                        }
+                        // Errors from middlewares are rendered later by actix, out
+                        // of this scope, so render them now while the id is known
+                        Err(err) => {
+                            let mut response = err.error_response();
+                            set_request_id_header(response.headers_mut(), &request_id);
+                            Err(InternalError::from_response(err, response).into())
+                        }
+                    };
+
+                    let status = match &result {
+                        Ok(res) => res.status(),
+                        Err(err) => err.as_response_error().status_code(),
+                    };
+                    tracing::info!(
+                        status = status.as_u16(),
+                        latency_ms = started_at.elapsed().as_millis() as u64,
+                        "request completed"
+                    );
+
+                    result
+                })
+                .instrument(span),
+        )
+    }
+}
+
+fn set_request_id_header(headers: &mut HeaderMap, request_id: &str) {
+    if let Ok(value) = HeaderValue::from_str(request_id) {
+        headers.insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+}
+
+fn is_valid_request_id(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LENGTH
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn generate_request_id() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
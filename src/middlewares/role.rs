@@ -0,0 +1,78 @@
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, HttpMessage};
+use futures_util::future::LocalBoxFuture;
+
+use crate::models::staff::Role;
+use crate::utils::common::AppError;
+
+// Must be wrapped inside `JwtMiddleware`, which puts the caller's role
+// into the request extensions.
+pub struct RequireRole {
+    roles: Vec<Role>,
+}
+
+impl RequireRole {
+    pub fn new(roles: &[Role]) -> Self {
+        RequireRole {
+            roles: roles.to_vec(),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequireRole
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequireRoleMiddleware<S>;
+    type Future = LocalBoxFuture<'static, Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let roles = self.roles.clone();
+        Box::pin(async move { Ok(RequireRoleMiddleware { service, roles }) })
+    }
+}
+
+pub struct RequireRoleMiddleware<S> {
+    service: S,
+    roles: Vec<Role>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireRoleMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let is_allowed = req
+            .extensions()
+            .get::<Role>()
+            .is_some_and(|role| self.roles.contains(role));
+
+        if !is_allowed {
+            return Box::pin(async move {
+                Err(AppError::Forbidden("Insufficient permissions.".to_string()).into())
+            });
+        }
+
+        let fut = self.service.call(req);
+
+        Box::pin(fut)
+    }
+}
@@ -0,0 +1,56 @@
+use crate::schema::audit_events;
+use chrono::{DateTime, Utc};
+use diesel::{Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEntity {
+    Room,
+    RoomType,
+    Reservation,
+    Staff,
+}
+
+impl AuditEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEntity::Room => "room",
+            AuditEntity::RoomType => "room_type",
+            AuditEntity::Reservation => "reservation",
+            AuditEntity::Staff => "staff",
+        }
+    }
+}
+
+#[derive(Queryable, Serialize, Debug)]
+pub struct AuditEvent {
+    pub id: i32,
+    pub actor_id: Option<i32>,
+    pub entity: String,
+    pub entity_id: i32,
+    pub action: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "audit_events"]
+pub struct NewAuditEvent<'a> {
+    pub actor_id: Option<i32>,
+    pub entity: &'a str,
+    pub entity_id: i32,
+    pub action: &'a str,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AuditSearchParams {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    pub entity: Option<AuditEntity>,
+    pub entity_id: Option<i32>,
+}
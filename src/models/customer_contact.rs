@@ -1,8 +1,15 @@
 use crate::schema::customer_contacts;
+use crate::utils::common::AppError;
+use crate::utils::validation::{Validate, ValidationErrors};
 use chrono::{DateTime, Utc};
 use diesel::{AsChangeset, Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 
+use super::reservation::Reservation;
+
+pub const MAX_FULL_NAME_LENGTH: usize = 100;
+pub const MAX_EMAIL_LENGTH: usize = 100;
+
 #[derive(Queryable, Serialize, Deserialize, Debug)]
 pub struct CustomerContact {
     pub id: i32,
@@ -31,3 +38,38 @@ pub struct UpdateCustomerContact<'a> {
     pub phone_number: &'a String,
     pub updated_at: &'a DateTime<Utc>,
 }
+
+#[derive(Deserialize, Debug)]
+pub struct CreateOrUpdateCustomerRequest {
+    pub full_name: String,
+    pub email: String,
+    pub phone_number: String,
+}
+
+impl Validate for CreateOrUpdateCustomerRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::new();
+        errors.required("full_name", &self.full_name, MAX_FULL_NAME_LENGTH);
+        errors.email("email", &self.email, MAX_EMAIL_LENGTH);
+        errors.phone_number("phone_number", &self.phone_number);
+        errors.into_result()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CustomerSearchParams {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    pub search: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MergeCustomersRequest {
+    pub duplicate_ids: Vec<i32>,
+}
+
+#[derive(Serialize)]
+pub struct CustomerWithReservations {
+    pub customer: CustomerContact,
+    pub reservations: Vec<Reservation>,
+}
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+use super::staff::Role;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: i32,
+    pub role: Role,
+    pub sid: i32,
     pub exp: usize,
 }
@@ -0,0 +1,19 @@
+use crate::schema::login_ip_attempts;
+use chrono::{DateTime, Utc};
+use diesel::{Insertable, Queryable};
+
+#[derive(Queryable, Debug)]
+pub struct LoginIpAttempt {
+    pub failed_attempts: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// First failure from an address, later ones increment the stored count
+#[derive(Insertable)]
+#[table_name = "login_ip_attempts"]
+pub struct NewLoginIpAttempt<'a> {
+    pub ip_address: &'a str,
+    pub failed_attempts: i32,
+    pub updated_at: &'a DateTime<Utc>,
+}
@@ -2,4 +2,8 @@ pub mod room;
 pub mod staff;
 pub mod jwt;
 pub mod reservation;
-pub mod customer_contact;
\ No newline at end of file
+pub mod customer_contact;
+pub mod staff_session;
+pub mod staff_password_reset;
+pub mod login_ip_attempt;
+pub mod audit_event;
\ No newline at end of file
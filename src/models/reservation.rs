@@ -1,13 +1,131 @@
 use crate::schema::reservations;
+use crate::utils::common::AppError;
+use crate::utils::validation::{Validate, ValidationErrors};
 use chrono::{DateTime, NaiveDate, Utc};
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::Varchar;
 use diesel::{AsChangeset, Insertable, Queryable};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+
+use super::customer_contact::{MAX_EMAIL_LENGTH, MAX_FULL_NAME_LENGTH};
+
+const MAX_CANCELLATION_REASON_LENGTH: usize = 500;
+const GUEST_FIELD_REQUIRED: &str = "is required without customer_contact_id";
 
 use super::{
     customer_contact::CustomerContact,
     room::{Room, RoomTypes},
 };
 
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Varchar)]
+#[serde(rename_all = "snake_case")]
+pub enum ReservationStatus {
+    Pending,
+    Confirmed,
+    CheckedIn,
+    CheckedOut,
+    Cancelled,
+    NoShow,
+}
+
+impl ReservationStatus {
+    // Statuses whose reservations keep the room booked for their dates
+    pub const HOLDING_INVENTORY: [ReservationStatus; 3] = [
+        ReservationStatus::Pending,
+        ReservationStatus::Confirmed,
+        ReservationStatus::CheckedIn,
+    ];
+
+    // Statuses shown as occupying their nights on the calendar, the inventory
+    // holding ones plus stays that have already ended
+    pub const OCCUPYING_NIGHTS: [ReservationStatus; 4] = [
+        ReservationStatus::Pending,
+        ReservationStatus::Confirmed,
+        ReservationStatus::CheckedIn,
+        ReservationStatus::CheckedOut,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReservationStatus::Pending => "pending",
+            ReservationStatus::Confirmed => "confirmed",
+            ReservationStatus::CheckedIn => "checked_in",
+            ReservationStatus::CheckedOut => "checked_out",
+            ReservationStatus::Cancelled => "cancelled",
+            ReservationStatus::NoShow => "no_show",
+        }
+    }
+
+    // A reservation can only be created as pending or already confirmed.
+    pub fn is_initial(&self) -> bool {
+        matches!(
+            self,
+            ReservationStatus::Pending | ReservationStatus::Confirmed
+        )
+    }
+
+    pub fn holds_inventory(&self) -> bool {
+        ReservationStatus::HOLDING_INVENTORY.contains(self)
+    }
+
+    pub fn can_transition_to(&self, next: ReservationStatus) -> bool {
+        use ReservationStatus::*;
+
+        matches!(
+            (self, next),
+            (Pending, Confirmed)
+                | (Pending, Cancelled)
+                | (Confirmed, CheckedIn)
+                | (Confirmed, Cancelled)
+                | (Confirmed, NoShow)
+                | (CheckedIn, CheckedOut)
+        )
+    }
+}
+
+impl fmt::Display for ReservationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ReservationStatus {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "pending" => Ok(ReservationStatus::Pending),
+            "confirmed" => Ok(ReservationStatus::Confirmed),
+            "checked_in" => Ok(ReservationStatus::CheckedIn),
+            "checked_out" => Ok(ReservationStatus::CheckedOut),
+            "cancelled" => Ok(ReservationStatus::Cancelled),
+            "no_show" => Ok(ReservationStatus::NoShow),
+            _ => Err(format!("Unknown reservation status: {}", value)),
+        }
+    }
+}
+
+impl ToSql<Varchar, Pg> for ReservationStatus {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Varchar, Pg> for ReservationStatus {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = <String as FromSql<Varchar, Pg>>::from_sql(bytes)?;
+        Ok(value.parse()?)
+    }
+}
+
 #[derive(Queryable, Serialize, Deserialize, Debug)]
 pub struct Reservation {
     pub id: i32,
@@ -16,7 +134,7 @@ pub struct Reservation {
     pub check_in_date: NaiveDate,
     pub check_out_date: NaiveDate,
     pub total_price: i32,
-    pub status: String,
+    pub status: ReservationStatus,
 
     pub created_by: Option<i32>,
     pub created_at: DateTime<Utc>,
@@ -29,6 +147,17 @@ pub struct Reservation {
 
     pub cancelled_by: Option<i32>,
     pub cancelled_at: Option<DateTime<Utc>>,
+
+    pub checked_in_by: Option<i32>,
+    pub checked_in_at: Option<DateTime<Utc>>,
+
+    pub checked_out_by: Option<i32>,
+    pub checked_out_at: Option<DateTime<Utc>>,
+
+    pub no_show_by: Option<i32>,
+    pub no_show_at: Option<DateTime<Utc>>,
+
+    pub cancellation_reason: Option<String>,
 }
 
 #[derive(Insertable)]
@@ -39,7 +168,7 @@ pub struct NewReservation<'a> {
     pub check_in_date: &'a NaiveDate,
     pub check_out_date: &'a NaiveDate,
     pub total_price: i32,
-    pub status: &'a String,
+    pub status: ReservationStatus,
 
     pub created_by: Option<i32>,
     pub created_at: &'a DateTime<Utc>,
@@ -59,29 +188,130 @@ pub struct CreateOrUpdateReservationRequest {
     pub room_id: i32,
     pub check_in_date: NaiveDate,
     pub check_out_date: NaiveDate,
-    pub status: String,
-    pub full_name: String,
-    pub email: String,
-    pub phone_number: String,
+    pub status: ReservationStatus,
+    pub customer_contact_id: Option<i32>,
+    pub full_name: Option<String>,
+    pub email: Option<String>,
+    pub phone_number: Option<String>,
+}
+
+impl Validate for CreateOrUpdateReservationRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::new();
+
+        if self.check_out_date <= self.check_in_date {
+            errors.add("check_out_date", "must be after check_in_date");
+        }
+
+        // Guest details are only needed when no existing contact is picked
+        let guest_required = self.customer_contact_id.is_none();
+
+        match &self.full_name {
+            Some(full_name) => errors.required("full_name", full_name, MAX_FULL_NAME_LENGTH),
+            None if guest_required => errors.add("full_name", GUEST_FIELD_REQUIRED),
+            None => {}
+        }
+
+        match &self.email {
+            Some(email) => errors.email("email", email, MAX_EMAIL_LENGTH),
+            None if guest_required => errors.add("email", GUEST_FIELD_REQUIRED),
+            None => {}
+        }
+
+        match &self.phone_number {
+            Some(phone_number) => errors.phone_number("phone_number", phone_number),
+            None if guest_required => errors.add("phone_number", GUEST_FIELD_REQUIRED),
+            None => {}
+        }
+
+        errors.into_result()
+    }
 }
 
 #[derive(AsChangeset)]
 #[table_name = "reservations"]
 pub struct UpdateReservation<'a> {
     pub room_id: &'a i32,
+    pub customer_contact_id: i32,
     pub check_in_date: &'a NaiveDate,
     pub check_out_date: &'a NaiveDate,
     pub total_price: i32,
-    pub status: &'a String,
 
     pub updated_by: Option<i32>,
     pub updated_at: &'a DateTime<Utc>,
+}
+
+// Only the `*_by`/`*_at` pair matching the new status is set, the other
+// lifecycle columns are left untouched.
+#[derive(AsChangeset, Default)]
+#[table_name = "reservations"]
+pub struct UpdateReservationStatus {
+    pub status: Option<ReservationStatus>,
 
     pub confirmed_by: Option<i32>,
     pub confirmed_at: Option<DateTime<Utc>>,
 
     pub cancelled_by: Option<i32>,
     pub cancelled_at: Option<DateTime<Utc>>,
+
+    pub checked_in_by: Option<i32>,
+    pub checked_in_at: Option<DateTime<Utc>>,
+
+    pub checked_out_by: Option<i32>,
+    pub checked_out_at: Option<DateTime<Utc>>,
+
+    pub no_show_by: Option<i32>,
+    pub no_show_at: Option<DateTime<Utc>>,
+
+    pub cancellation_reason: Option<String>,
+}
+
+impl UpdateReservationStatus {
+    pub fn new(status: ReservationStatus, staff_id: i32, now: DateTime<Utc>) -> Self {
+        let mut data = UpdateReservationStatus {
+            status: Some(status),
+            ..Default::default()
+        };
+
+        match status {
+            ReservationStatus::Pending => {}
+            ReservationStatus::Confirmed => {
+                data.confirmed_by = Some(staff_id);
+                data.confirmed_at = Some(now);
+            }
+            ReservationStatus::CheckedIn => {
+                data.checked_in_by = Some(staff_id);
+                data.checked_in_at = Some(now);
+            }
+            ReservationStatus::CheckedOut => {
+                data.checked_out_by = Some(staff_id);
+                data.checked_out_at = Some(now);
+            }
+            ReservationStatus::Cancelled => {
+                data.cancelled_by = Some(staff_id);
+                data.cancelled_at = Some(now);
+            }
+            ReservationStatus::NoShow => {
+                data.no_show_by = Some(staff_id);
+                data.no_show_at = Some(now);
+            }
+        }
+
+        data
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CancelReservationRequest {
+    pub reason: String,
+}
+
+impl Validate for CancelReservationRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::new();
+        errors.required("reason", &self.reason, MAX_CANCELLATION_REASON_LENGTH);
+        errors.into_result()
+    }
 }
 
 #[derive(Serialize)]
@@ -91,3 +321,87 @@ pub struct ReservationWithJoin {
     pub room_type: Option<RoomTypes>,
     pub customer_contact: Option<CustomerContact>,
 }
+
+#[derive(Deserialize, Debug)]
+pub struct CalendarParams {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[derive(Queryable, Serialize, Clone, Debug)]
+pub struct CalendarReservation {
+    pub id: i32,
+    pub status: ReservationStatus,
+    pub check_in_date: NaiveDate,
+    pub check_out_date: NaiveDate,
+    pub guest_name: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub reservations: Vec<CalendarReservation>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CalendarRoom {
+    pub room: Room,
+    pub room_type: Option<RoomTypes>,
+    pub days: Vec<CalendarDay>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(check_in_date: &str, check_out_date: &str) -> CreateOrUpdateReservationRequest {
+        CreateOrUpdateReservationRequest {
+            room_id: 1,
+            check_in_date: check_in_date.parse().unwrap(),
+            check_out_date: check_out_date.parse().unwrap(),
+            status: ReservationStatus::Pending,
+            customer_contact_id: Some(1),
+            full_name: None,
+            email: None,
+            phone_number: None,
+        }
+    }
+
+    fn invalid_fields(request: &CreateOrUpdateReservationRequest) -> Vec<String> {
+        match request.validate() {
+            Ok(()) => Vec::new(),
+            Err(AppError::Validation(errors)) => serde_json::to_value(errors)
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect(),
+            Err(err) => panic!("expected a validation error, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn check_out_must_come_after_check_in() {
+        assert!(invalid_fields(&request("2030-01-10", "2030-01-11")).is_empty());
+        assert_eq!(
+            invalid_fields(&request("2030-01-10", "2030-01-10")),
+            ["check_out_date"]
+        );
+        assert_eq!(
+            invalid_fields(&request("2030-01-10", "2030-01-09")),
+            ["check_out_date"]
+        );
+    }
+
+    #[test]
+    fn guest_details_are_required_without_a_contact() {
+        let mut without_contact = request("2030-01-10", "2030-01-11");
+        without_contact.customer_contact_id = None;
+
+        assert_eq!(
+            invalid_fields(&without_contact),
+            ["email", "full_name", "phone_number"]
+        );
+    }
+}
@@ -1,8 +1,13 @@
 use crate::schema::{room_types, rooms};
-use chrono::{DateTime, Utc};
+use crate::utils::common::AppError;
+use crate::utils::validation::{Validate, ValidationErrors};
+use chrono::{DateTime, NaiveDate, Utc};
 use diesel::{AsChangeset, Insertable, Queryable};
 use serde::{Deserialize, Serialize};
 
+const MAX_ROOM_NAME_LENGTH: usize = 100;
+const MAX_TYPE_NAME_LENGTH: usize = 50;
+
 #[derive(Queryable, Serialize, Deserialize, Debug)]
 pub struct Room {
     pub id: i32,
@@ -37,6 +42,15 @@ pub struct CreateRoomRequest {
     pub is_available: bool,
 }
 
+impl Validate for CreateRoomRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::new();
+        errors.required("room_name", &self.room_name, MAX_ROOM_NAME_LENGTH);
+        errors.positive("capacity", self.capacity);
+        errors.into_result()
+    }
+}
+
 #[derive(AsChangeset)]
 #[table_name = "rooms"]
 pub struct UpdateRoomData {
@@ -56,6 +70,15 @@ pub struct UpdateRoomRequest {
     pub is_available: bool,
 }
 
+impl Validate for UpdateRoomRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::new();
+        errors.required("room_name", &self.room_name, MAX_ROOM_NAME_LENGTH);
+        errors.positive("capacity", self.capacity);
+        errors.into_result()
+    }
+}
+
 #[derive(Queryable, Serialize, Deserialize, Debug)]
 pub struct RoomTypes {
     pub id: i32,
@@ -88,6 +111,15 @@ pub struct CreateOrUpdateRoomTypesRequest {
     pub room_ids: Option<Vec<i32>>,
 }
 
+impl Validate for CreateOrUpdateRoomTypesRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::new();
+        errors.required("type_name", &self.type_name, MAX_TYPE_NAME_LENGTH);
+        errors.positive("price_per_night", self.price_per_night);
+        errors.into_result()
+    }
+}
+
 #[derive(AsChangeset)]
 #[table_name = "room_types"]
 pub struct UpdateRoomTypeData {
@@ -103,3 +135,33 @@ pub struct RoomWithType {
     pub id: i32,
     pub price_per_night: i32,
 }
+
+#[derive(Deserialize, Debug)]
+pub struct RoomAvailabilityParams {
+    pub check_in: NaiveDate,
+    pub check_out: NaiveDate,
+    pub guests: Option<i32>,
+    pub type_id: Option<i32>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RoomTypeSummary {
+    pub room_type: RoomTypes,
+    pub room_count: i64,
+    // Rooms open for sale with nobody booked in for tonight
+    pub available_room_count: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RoomTypeWithRooms {
+    pub room_type: RoomTypes,
+    pub rooms: Vec<Room>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AvailableRoomType {
+    pub room_type: RoomTypes,
+    pub nights: i64,
+    pub total_price: i32,
+    pub rooms: Vec<Room>,
+}
@@ -1,26 +1,77 @@
 use crate::schema::staff;
+use crate::utils::common::AppError;
+use crate::utils::validation::{Validate, ValidationErrors};
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_EMAIL_LENGTH: usize = 100;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    Manager,
+    FrontDesk,
+    Housekeeping,
+}
+
+impl Role {
+    pub const MANAGEMENT: [Role; 2] = [Role::Admin, Role::Manager];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Manager => "manager",
+            Role::FrontDesk => "front_desk",
+            Role::Housekeeping => "housekeeping",
+        }
+    }
+
+    // Positions saved before roles existed are free text, so they are matched
+    // loosely and anything unknown gets the least privileged role.
+    pub fn from_position(position: &str) -> Role {
+        let normalized = position.trim().to_lowercase().replace([' ', '-'], "_");
+
+        match normalized.as_str() {
+            "admin" | "administrator" => Role::Admin,
+            "manager" => Role::Manager,
+            "front_desk" | "receptionist" => Role::FrontDesk,
+            _ => Role::Housekeeping,
+        }
+    }
+
+    // Only admins may hand out or manage admin accounts
+    pub fn can_manage(&self, role: Role) -> bool {
+        *self == Role::Admin || role != Role::Admin
+    }
+}
+
 #[derive(Queryable, Serialize, Deserialize)]
 pub struct Staff {
     pub id: i32,
     pub name: String,
     pub email: String,
+    #[serde(skip_serializing)]
     pub password: String,
     pub position: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub is_active: bool,
+    pub must_change_password: bool,
+    pub failed_login_attempts: i32,
+    pub locked_until: Option<DateTime<Utc>>,
 }
 
 #[derive(Insertable)]
 #[table_name = "staff"]
 pub struct NewStaff<'a> {
-    pub name: &'a String,
-    pub email: &'a String,
-    pub password: &'a String,
-    pub position: &'a String,
+    pub name: &'a str,
+    pub email: &'a str,
+    pub password: &'a str,
+    pub position: &'a str,
+    pub must_change_password: bool,
     pub created_at: &'a DateTime<Utc>,
     pub updated_at: &'a DateTime<Utc>,
 }
@@ -30,7 +81,17 @@ pub struct CreateStaffRequest {
     pub name: String,
     pub email: String,
     pub password: String,
-    pub position: String,
+    pub position: Role,
+}
+
+impl Validate for CreateStaffRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::new();
+        errors.required("name", &self.name, MAX_NAME_LENGTH);
+        errors.email("email", &self.email, MAX_EMAIL_LENGTH);
+        errors.password("password", &self.password);
+        errors.into_result()
+    }
 }
 
 #[derive(AsChangeset)]
@@ -39,6 +100,8 @@ pub struct UpdateStaffData {
     pub name: String,
     pub password: Option<String>,
     pub position: String,
+    pub is_active: Option<bool>,
+    pub must_change_password: Option<bool>,
     pub updated_at: DateTime<Utc>,
 }
 
@@ -46,7 +109,21 @@ pub struct UpdateStaffData {
 pub struct UpdateStaffRequest {
     pub name: String,
     pub password: Option<String>,
-    pub position: String,
+    pub position: Role,
+    pub is_active: Option<bool>,
+}
+
+impl Validate for UpdateStaffRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::new();
+        errors.required("name", &self.name, MAX_NAME_LENGTH);
+
+        if let Some(password) = &self.password {
+            errors.password("password", password);
+        }
+
+        errors.into_result()
+    }
 }
 
 #[derive(Deserialize)]
@@ -54,3 +131,17 @@ pub struct LoginRequest {
     pub email: String,
     pub password: String,
 }
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+impl Validate for ChangePasswordRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::new();
+        errors.password("new_password", &self.new_password);
+        errors.into_result()
+    }
+}
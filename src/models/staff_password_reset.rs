@@ -0,0 +1,39 @@
+use crate::schema::staff_password_resets;
+use crate::utils::common::AppError;
+use crate::utils::validation::{Validate, ValidationErrors};
+use chrono::{DateTime, Utc};
+use diesel::Insertable;
+use serde::Deserialize;
+
+#[derive(Insertable)]
+#[table_name = "staff_password_resets"]
+pub struct NewStaffPasswordReset<'a> {
+    pub staff_id: i32,
+    pub token_hash: &'a String,
+    pub expires_at: &'a DateTime<Utc>,
+    pub created_at: &'a DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct RequestPasswordResetRequest {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyPasswordResetRequest {
+    pub token: String,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmPasswordResetRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+impl Validate for ConfirmPasswordResetRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        let mut errors = ValidationErrors::new();
+        errors.password("new_password", &self.new_password);
+        errors.into_result()
+    }
+}
@@ -0,0 +1,25 @@
+use crate::schema::staff_sessions;
+use chrono::{DateTime, Utc};
+use diesel::Insertable;
+use serde::{Deserialize, Serialize};
+
+#[derive(Insertable)]
+#[table_name = "staff_sessions"]
+pub struct NewStaffSession<'a> {
+    pub staff_id: i32,
+    pub refresh_token_hash: &'a String,
+    pub expires_at: &'a DateTime<Utc>,
+    pub created_at: &'a DateTime<Utc>,
+    pub updated_at: &'a DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Serialize)]
+pub struct AuthTokens {
+    pub token: String,
+    pub refresh_token: String,
+}
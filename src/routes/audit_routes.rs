@@ -0,0 +1,14 @@
+use crate::handlers::audit_handler::get_audit_events_with_pagination_handler;
+use crate::middlewares::auth::JwtMiddleware;
+use crate::middlewares::role::RequireRole;
+use crate::models::staff::Role;
+use actix_web::web;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/audit")
+            .wrap(RequireRole::new(&Role::MANAGEMENT))
+            .wrap(JwtMiddleware::new())
+            .route("", web::get().to(get_audit_events_with_pagination_handler)),
+    );
+}
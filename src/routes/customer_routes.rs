@@ -0,0 +1,18 @@
+use crate::handlers::customer_handler::{
+    create_customer_handler, get_customer_by_id_handler, get_customers_with_pagination_handler,
+    merge_customers_handler, update_customer_by_id_handler,
+};
+use crate::middlewares::auth::JwtMiddleware;
+use actix_web::web;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/customers")
+            .wrap(JwtMiddleware::new())
+            .route("/create", web::post().to(create_customer_handler))
+            .route("{id}/merge", web::post().to(merge_customers_handler))
+            .route("{id}", web::get().to(get_customer_by_id_handler))
+            .route("{id}", web::put().to(update_customer_by_id_handler))
+            .route("", web::post().to(get_customers_with_pagination_handler)),
+    );
+}
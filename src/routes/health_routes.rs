@@ -0,0 +1,10 @@
+use crate::handlers::health_handler::{health_handler, ready_handler, version_handler};
+use crate::handlers::metrics_handler::metrics_handler;
+use actix_web::web;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/health", web::get().to(health_handler))
+        .route("/ready", web::get().to(ready_handler))
+        .route("/version", web::get().to(version_handler))
+        .route("/metrics", web::get().to(metrics_handler));
+}
@@ -1,4 +1,8 @@
+#[allow(clippy::module_inception)]
 pub mod routes;
 pub mod room_routes;
 pub mod staff_routes;
-pub mod reservation_routes;
\ No newline at end of file
+pub mod reservation_routes;
+pub mod customer_routes;
+pub mod audit_routes;
+pub mod health_routes;
\ No newline at end of file
@@ -1,6 +1,7 @@
-use crate::config::auth::staff_jwt_secret;
 use crate::handlers::reservation_handler::{
-    create_reservation_handler, get_reservations_with_pagination_handler,
+    cancel_reservation_handler, check_in_reservation_handler, check_out_reservation_handler,
+    confirm_reservation_handler, create_reservation_handler, get_reservation_by_id_handler,
+    get_reservation_calendar_handler, get_reservations_with_pagination_handler,
     update_reservation_by_id_handler,
 };
 use crate::middlewares::auth::JwtMiddleware;
@@ -9,9 +10,25 @@ use actix_web::web;
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/reservations")
-            .wrap(JwtMiddleware::new(staff_jwt_secret()))
+            .wrap(JwtMiddleware::new())
             .route("/create", web::post().to(create_reservation_handler))
+            .route("{id}", web::get().to(get_reservation_by_id_handler))
             .route("{id}", web::put().to(update_reservation_by_id_handler))
+            .route("{id}/confirm", web::post().to(confirm_reservation_handler))
+            .route("{id}/cancel", web::post().to(cancel_reservation_handler))
+            .route(
+                "{id}/check-in",
+                web::post().to(check_in_reservation_handler),
+            )
+            .route(
+                "{id}/check-out",
+                web::post().to(check_out_reservation_handler),
+            )
             .route("", web::post().to(get_reservations_with_pagination_handler)),
+    )
+    .service(
+        web::scope("/calendar")
+            .wrap(JwtMiddleware::new())
+            .route("", web::get().to(get_reservation_calendar_handler)),
     );
 }
@@ -1,23 +1,41 @@
-use crate::config::auth::staff_jwt_secret;
 use crate::handlers::room_handler::{
-    create_room_handler, create_room_type_handler, get_rooms_with_pagination_handler,
-    update_room_by_id_handler, update_room_type_by_id_handler,
+    create_room_handler, create_room_type_handler, get_available_rooms_handler,
+    get_room_by_id_handler, get_room_type_by_id_handler, get_room_types_with_pagination_handler,
+    get_rooms_with_pagination_handler, update_room_by_id_handler, update_room_type_by_id_handler,
 };
 use crate::middlewares::auth::JwtMiddleware;
+use crate::middlewares::role::RequireRole;
+use crate::models::staff::Role;
 use actix_web::web;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/rooms")
-            .wrap(JwtMiddleware::new(staff_jwt_secret()))
+            .wrap(JwtMiddleware::new())
             .route("/create", web::post().to(create_room_handler))
+            .route("/availability", web::get().to(get_available_rooms_handler))
+            .route("{id}", web::get().to(get_room_by_id_handler))
             .route("{id}", web::put().to(update_room_by_id_handler))
             .route("", web::post().to(get_rooms_with_pagination_handler)),
     )
     .service(
+        // Any staff may look room types up, only management may change them
         web::scope("/room-types")
-            .wrap(JwtMiddleware::new(staff_jwt_secret()))
-            .route("/create", web::post().to(create_room_type_handler))
-            .route("{id}", web::put().to(update_room_type_by_id_handler)),
+            .wrap(JwtMiddleware::new())
+            .service(
+                web::resource("/create")
+                    .wrap(RequireRole::new(&Role::MANAGEMENT))
+                    .route(web::post().to(create_room_type_handler)),
+            )
+            .service(
+                web::resource("{id}")
+                    .route(web::get().to(get_room_type_by_id_handler))
+                    .route(
+                        web::put()
+                            .to(update_room_type_by_id_handler)
+                            .wrap(RequireRole::new(&Role::MANAGEMENT)),
+                    ),
+            )
+            .route("", web::get().to(get_room_types_with_pagination_handler)),
     );
 }
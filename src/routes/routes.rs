@@ -1,13 +1,18 @@
 use crate::routes::{room_routes, staff_routes};
 use actix_web::web;
 
-use super::reservation_routes;
+use super::{audit_routes, customer_routes, health_routes, reservation_routes};
 
 pub fn init_routes(cfg: &mut web::ServiceConfig) {
+    // Probes and metrics for the orchestrator, kept outside /api and its auth
+    cfg.configure(health_routes::config);
+
     cfg.service(
         web::scope("/api")
             .configure(room_routes::config)
             .configure(staff_routes::config)
-            .configure(reservation_routes::config),
+            .configure(reservation_routes::config)
+            .configure(customer_routes::config)
+            .configure(audit_routes::config),
     );
 }
@@ -1,19 +1,54 @@
-use crate::config::auth::staff_jwt_secret;
 use crate::handlers::staff_handler::{
-    create_staff_handler, login_staff_handler, update_staff_by_id_handler,
+    change_own_password_handler, confirm_password_reset_handler, create_staff_handler,
+    get_staff_by_id_handler, login_staff_handler, logout_staff_handler,
+    refresh_staff_token_handler, request_password_reset_handler, unlock_staff_by_id_handler,
+    update_staff_by_id_handler, verify_password_reset_handler,
 };
 use crate::middlewares::auth::JwtMiddleware;
+use crate::middlewares::role::RequireRole;
+use crate::models::staff::Role;
 use actix_web::web;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/staffs")
             .route("/login", web::post().to(login_staff_handler))
+            .route("/refresh", web::post().to(refresh_staff_token_handler))
+            .service(
+                web::scope("/password-reset")
+                    .route("/request", web::post().to(request_password_reset_handler))
+                    .route("/verify", web::post().to(verify_password_reset_handler))
+                    .route("/confirm", web::post().to(confirm_password_reset_handler)),
+            )
+            .service(
+                web::resource("/logout")
+                    .wrap(JwtMiddleware::new().allow_password_change_pending())
+                    .route(web::post().to(logout_staff_handler)),
+            )
+            .service(
+                web::resource("/me/password")
+                    .wrap(JwtMiddleware::new().allow_password_change_pending())
+                    .route(web::put().to(change_own_password_handler)),
+            )
             .service(
                 web::scope("")
-                    .wrap(JwtMiddleware::new(staff_jwt_secret()))
-                    .route("/create", web::post().to(create_staff_handler))
-                    .route("{id}", web::put().to(update_staff_by_id_handler)),
+                    .wrap(JwtMiddleware::new())
+                    .service(
+                        web::resource("/create")
+                            .wrap(RequireRole::new(&Role::MANAGEMENT))
+                            .route(web::post().to(create_staff_handler)),
+                    )
+                    .service(
+                        web::resource("{id}")
+                            .wrap(RequireRole::new(&Role::MANAGEMENT))
+                            .route(web::get().to(get_staff_by_id_handler))
+                            .route(web::put().to(update_staff_by_id_handler)),
+                    )
+                    .service(
+                        web::resource("{id}/unlock")
+                            .wrap(RequireRole::new(&[Role::Admin]))
+                            .route(web::post().to(unlock_staff_by_id_handler)),
+                    ),
             ),
     );
 }
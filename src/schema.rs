@@ -1,5 +1,20 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audit_events (id) {
+        id -> Int4,
+        actor_id -> Nullable<Int4>,
+        #[max_length = 50]
+        entity -> Varchar,
+        entity_id -> Int4,
+        #[max_length = 50]
+        action -> Varchar,
+        before -> Nullable<Jsonb>,
+        after -> Nullable<Jsonb>,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     customer_contacts (id) {
         id -> Int4,
@@ -14,6 +29,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    login_ip_attempts (ip_address) {
+        #[max_length = 45]
+        ip_address -> Varchar,
+        failed_attempts -> Int4,
+        locked_until -> Nullable<Timestamptz>,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     reservations (id) {
         id -> Int4,
@@ -32,6 +57,13 @@ diesel::table! {
         confirmed_at -> Nullable<Timestamptz>,
         cancelled_by -> Nullable<Int4>,
         cancelled_at -> Nullable<Timestamptz>,
+        checked_in_by -> Nullable<Int4>,
+        checked_in_at -> Nullable<Timestamptz>,
+        checked_out_by -> Nullable<Int4>,
+        checked_out_at -> Nullable<Timestamptz>,
+        no_show_by -> Nullable<Int4>,
+        no_show_at -> Nullable<Timestamptz>,
+        cancellation_reason -> Nullable<Text>,
     }
 }
 
@@ -73,17 +105,53 @@ diesel::table! {
         position -> Varchar,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        is_active -> Bool,
+        must_change_password -> Bool,
+        failed_login_attempts -> Int4,
+        locked_until -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    staff_password_resets (id) {
+        id -> Int4,
+        staff_id -> Int4,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        expires_at -> Timestamptz,
+        used_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    staff_sessions (id) {
+        id -> Int4,
+        staff_id -> Int4,
+        #[max_length = 64]
+        refresh_token_hash -> Varchar,
+        expires_at -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
+diesel::joinable!(audit_events -> staff (actor_id));
 diesel::joinable!(reservations -> customer_contacts (customer_contact_id));
 diesel::joinable!(reservations -> rooms (room_id));
 diesel::joinable!(rooms -> room_types (type_id));
+diesel::joinable!(staff_password_resets -> staff (staff_id));
+diesel::joinable!(staff_sessions -> staff (staff_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    audit_events,
     customer_contacts,
+    login_ip_attempts,
     reservations,
     room_types,
     rooms,
     staff,
+    staff_password_resets,
+    staff_sessions,
 );
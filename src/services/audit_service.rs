@@ -0,0 +1,99 @@
+use crate::models::audit_event::{AuditEntity, AuditEvent, AuditSearchParams, NewAuditEvent};
+use crate::schema::audit_events;
+use crate::utils::common::AppError;
+use crate::utils::response::PaginationMeta;
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+// Records a write made by `actor_id`. When both snapshots are given only the
+// fields that changed are kept, so `before`/`after` read as a diff.
+pub fn record_audit_event<T: Serialize>(
+    conn: &mut PgConnection,
+    actor_id: Option<i32>,
+    entity: AuditEntity,
+    entity_id: i32,
+    action: &str,
+    before: Option<&T>,
+    after: Option<&T>,
+) -> Result<(), AppError> {
+    let mut before = before.map(to_json);
+    let mut after = after.map(to_json);
+
+    if let (Some(Value::Object(before_fields)), Some(Value::Object(after_fields))) =
+        (&mut before, &mut after)
+    {
+        let unchanged = before_fields
+            .iter()
+            .filter(|(key, value)| after_fields.get(*key) == Some(*value))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+
+        for key in unchanged {
+            before_fields.remove(&key);
+            after_fields.remove(&key);
+        }
+    }
+
+    let new_audit_event = NewAuditEvent {
+        actor_id,
+        entity: entity.as_str(),
+        entity_id,
+        action,
+        before,
+        after,
+    };
+
+    diesel::insert_into(audit_events::table)
+        .values(&new_audit_event)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or_else(|_| Value::Object(Map::new()))
+}
+
+fn filtered_audit_events<'a>(params: &AuditSearchParams) -> audit_events::BoxedQuery<'a, Pg> {
+    let mut query = audit_events::table.into_boxed();
+
+    if let Some(entity) = params.entity {
+        query = query.filter(audit_events::entity.eq(entity.as_str()));
+    }
+
+    if let Some(entity_id) = params.entity_id {
+        query = query.filter(audit_events::entity_id.eq(entity_id));
+    }
+
+    query
+}
+
+pub fn get_audit_events_with_pagination(
+    conn: &mut PgConnection,
+    params: &AuditSearchParams,
+    page: i64,
+    page_size: i64,
+) -> Result<(Vec<AuditEvent>, PaginationMeta), AppError> {
+    let total_items = filtered_audit_events(params)
+        .count()
+        .get_result::<i64>(conn)?;
+    let total_pages = (total_items as f64 / page_size as f64).ceil() as i64;
+
+    let offset = (page - 1) * page_size;
+    let audit_events_data = filtered_audit_events(params)
+        .order((audit_events::created_at.desc(), audit_events::id.desc()))
+        .limit(page_size)
+        .offset(offset)
+        .load::<AuditEvent>(conn)?;
+
+    let pagination_meta = PaginationMeta {
+        total_items,
+        total_pages,
+        current_page: page,
+        page_size,
+    };
+
+    Ok((audit_events_data, pagination_meta))
+}
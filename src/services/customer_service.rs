@@ -0,0 +1,290 @@
+use crate::models::customer_contact::{
+    CreateOrUpdateCustomerRequest, CustomerContact, CustomerWithReservations,
+    MergeCustomersRequest, NewCustomerContact, UpdateCustomerContact,
+};
+use crate::models::reservation::{CreateOrUpdateReservationRequest, Reservation};
+use crate::schema::{customer_contacts, reservations};
+use crate::utils::common::{AppError, DieselResultExt};
+use crate::utils::response::PaginationMeta;
+use chrono::Utc;
+use diesel::pg::Pg;
+use diesel::prelude::*;
+
+pub fn normalize_email(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+pub fn normalize_phone_number(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '+')
+        .collect()
+}
+
+// Returns the id of the guest a reservation belongs to. An explicit
+// customer_contact_id wins, otherwise a contact with the same email is reused,
+// taking on the submitted name and phone number. A matching phone number alone
+// is not enough to tell guests apart, so it gets a new contact that staff can
+// merge later.
+pub fn resolve_customer_contact(
+    conn: &mut PgConnection,
+    data: &CreateOrUpdateReservationRequest,
+) -> Result<i32, AppError> {
+    if let Some(contact_id) = data.customer_contact_id {
+        return customer_contacts::table
+            .find(contact_id)
+            .select(customer_contacts::id)
+            .first::<i32>(conn)
+            .optional()?
+            .ok_or_else(|| AppError::BadRequest("Customer contact not found.".to_string()));
+    }
+
+    let (Some(full_name), Some(email), Some(phone_number)) =
+        (&data.full_name, &data.email, &data.phone_number)
+    else {
+        return Err(AppError::BadRequest(
+            "full_name, email and phone_number are required without customer_contact_id."
+                .to_string(),
+        ));
+    };
+
+    let email = normalize_email(email);
+    let phone_number = normalize_phone_number(phone_number);
+
+    let existing = customer_contacts::table
+        .filter(customer_contacts::email.eq(&email))
+        .order(customer_contacts::id)
+        .first::<CustomerContact>(conn)
+        .optional()?;
+
+    let now = Utc::now();
+
+    // The submitted details are the latest we know about the guest, so a
+    // corrected name or a new phone number replaces what was stored
+    if let Some(contact) = existing {
+        if contact.full_name != *full_name || contact.phone_number != phone_number {
+            let update_customer_contact = UpdateCustomerContact {
+                full_name,
+                email: &email,
+                phone_number: &phone_number,
+                updated_at: &now,
+            };
+
+            diesel::update(customer_contacts::table.find(contact.id))
+                .set(update_customer_contact)
+                .execute(conn)?;
+        }
+
+        return Ok(contact.id);
+    }
+
+    let new_customer_contact = NewCustomerContact {
+        full_name,
+        email: &email,
+        phone_number: &phone_number,
+        created_at: &now,
+        updated_at: &now,
+    };
+
+    let customer_contact = diesel::insert_into(customer_contacts::table)
+        .values(&new_customer_contact)
+        .get_result::<CustomerContact>(conn)?;
+
+    Ok(customer_contact.id)
+}
+
+fn search_customers_query<'a>(search: Option<&str>) -> customer_contacts::BoxedQuery<'a, Pg> {
+    let mut query = customer_contacts::table.into_boxed();
+
+    if let Some(term) = search.map(str::trim).filter(|term| !term.is_empty()) {
+        let pattern = format!("%{}%", term);
+        query = query.filter(
+            customer_contacts::full_name
+                .ilike(pattern.clone())
+                .or(customer_contacts::email.ilike(pattern.clone()))
+                .or(customer_contacts::phone_number.ilike(pattern)),
+        );
+    }
+
+    query
+}
+
+pub fn get_customers_with_pagination(
+    conn: &mut PgConnection,
+    search: Option<&str>,
+    page: i64,
+    page_size: i64,
+) -> Result<(Vec<CustomerContact>, PaginationMeta), AppError> {
+    let total_items = search_customers_query(search)
+        .count()
+        .get_result::<i64>(conn)?;
+    let total_pages = (total_items as f64 / page_size as f64).ceil() as i64;
+    let offset = (page - 1) * page_size;
+
+    let customers = search_customers_query(search)
+        .order((customer_contacts::full_name, customer_contacts::id))
+        .limit(page_size)
+        .offset(offset)
+        .load::<CustomerContact>(conn)?;
+
+    let pagination_meta = PaginationMeta {
+        total_items,
+        total_pages,
+        current_page: page,
+        page_size,
+    };
+
+    Ok((customers, pagination_meta))
+}
+
+pub fn get_customer_by_id(
+    conn: &mut PgConnection,
+    customer_id: i32,
+) -> Result<CustomerWithReservations, AppError> {
+    let customer = customer_contacts::table
+        .find(customer_id)
+        .first::<CustomerContact>(conn)
+        .or_not_found("Customer not found.")?;
+
+    let reservations_data = reservations::table
+        .filter(reservations::customer_contact_id.eq(customer_id))
+        .order(reservations::check_in_date.desc())
+        .load::<Reservation>(conn)?;
+
+    Ok(CustomerWithReservations {
+        customer,
+        reservations: reservations_data,
+    })
+}
+
+fn check_duplicate_email(
+    conn: &mut PgConnection,
+    email: &str,
+    customer_id: Option<i32>,
+) -> Result<(), AppError> {
+    let mut query = customer_contacts::table
+        .filter(customer_contacts::email.eq(email))
+        .into_boxed();
+
+    if let Some(customer_id) = customer_id {
+        query = query.filter(customer_contacts::id.ne(customer_id));
+    }
+
+    let duplicate = query
+        .select(customer_contacts::id)
+        .first::<i32>(conn)
+        .optional()?;
+
+    match duplicate {
+        Some(_) => Err(AppError::Conflict(
+            "Customer email already exists.".to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+pub fn create_customer(
+    conn: &mut PgConnection,
+    data: &CreateOrUpdateCustomerRequest,
+) -> Result<(), AppError> {
+    let now = Utc::now();
+    let email = normalize_email(&data.email);
+    let phone_number = normalize_phone_number(&data.phone_number);
+
+    check_duplicate_email(conn, &email, None)?;
+
+    let new_customer_contact = NewCustomerContact {
+        full_name: &data.full_name,
+        email: &email,
+        phone_number: &phone_number,
+        created_at: &now,
+        updated_at: &now,
+    };
+
+    diesel::insert_into(customer_contacts::table)
+        .values(&new_customer_contact)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+pub fn update_customer_by_id(
+    conn: &mut PgConnection,
+    customer_id: i32,
+    data: &CreateOrUpdateCustomerRequest,
+) -> Result<(), AppError> {
+    customer_contacts::table
+        .find(customer_id)
+        .first::<CustomerContact>(conn)
+        .or_not_found("Customer not found.")?;
+
+    let now = Utc::now();
+    let email = normalize_email(&data.email);
+    let phone_number = normalize_phone_number(&data.phone_number);
+
+    check_duplicate_email(conn, &email, Some(customer_id))?;
+
+    let update_customer_contact = UpdateCustomerContact {
+        full_name: &data.full_name,
+        email: &email,
+        phone_number: &phone_number,
+        updated_at: &now,
+    };
+
+    diesel::update(customer_contacts::table.find(customer_id))
+        .set(update_customer_contact)
+        .execute(conn)?;
+
+    Ok(())
+}
+
+// Moves every reservation of the duplicates onto the kept customer and
+// removes the duplicate contacts.
+pub fn merge_customers(
+    conn: &mut PgConnection,
+    customer_id: i32,
+    data: &MergeCustomersRequest,
+) -> Result<(), AppError> {
+    if data.duplicate_ids.is_empty() || data.duplicate_ids.contains(&customer_id) {
+        return Err(AppError::BadRequest(
+            "duplicate_ids must list other customers to merge.".to_string(),
+        ));
+    }
+
+    // A repeated id is still one customer to merge
+    let mut duplicate_ids = data.duplicate_ids.clone();
+    duplicate_ids.sort_unstable();
+    duplicate_ids.dedup();
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        customer_contacts::table
+            .find(customer_id)
+            .first::<CustomerContact>(conn)
+            .or_not_found("Customer not found.")?;
+
+        let found = customer_contacts::table
+            .filter(customer_contacts::id.eq_any(&duplicate_ids))
+            .count()
+            .get_result::<i64>(conn)?;
+
+        if found != duplicate_ids.len() as i64 {
+            return Err(AppError::BadRequest(
+                "Some duplicate customers were not found.".to_string(),
+            ));
+        }
+
+        diesel::update(
+            reservations::table
+                .filter(reservations::customer_contact_id.eq_any(&duplicate_ids)),
+        )
+        .set(reservations::customer_contact_id.eq(customer_id))
+        .execute(conn)?;
+
+        diesel::delete(
+            customer_contacts::table.filter(customer_contacts::id.eq_any(&duplicate_ids)),
+        )
+        .execute(conn)?;
+
+        Ok(())
+    })
+}
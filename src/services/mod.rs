@@ -1,3 +1,5 @@
 pub mod room_service;
 pub mod staff_service;
-pub mod reservation_service;
\ No newline at end of file
+pub mod reservation_service;
+pub mod customer_service;
+pub mod audit_service;
\ No newline at end of file
@@ -1,44 +1,55 @@
-use crate::models::customer_contact::{CustomerContact, NewCustomerContact, UpdateCustomerContact};
+use crate::models::audit_event::AuditEntity;
+use crate::models::customer_contact::CustomerContact;
 use crate::models::reservation::{
-    CreateOrUpdateReservationRequest, NewReservation, Reservation, ReservationWithJoin,
-    UpdateReservation,
+    CalendarDay, CalendarParams, CalendarReservation, CalendarRoom,
+    CreateOrUpdateReservationRequest, NewReservation, Reservation, ReservationStatus,
+    ReservationWithJoin, UpdateReservation, UpdateReservationStatus,
 };
 use crate::models::room::{Room, RoomTypes, RoomWithType};
 use crate::schema::{customer_contacts, reservations, room_types, rooms};
-use crate::utils::common::AppError;
+use crate::services::audit_service::record_audit_event;
+use crate::services::customer_service::resolve_customer_contact;
+use crate::utils::common::{AppError, DieselResultExt};
 use crate::utils::response::PaginationMeta;
-use chrono::{NaiveDate, Utc};
-use diesel::dsl::sql;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::pg::Pg;
 use diesel::prelude::*;
-use diesel::sql_types::Bool;
+use std::collections::HashMap;
 
-fn check_overlapping(
+type ReservationJoinRow = (
+    Reservation,
+    Option<Room>,
+    Option<RoomTypes>,
+    Option<CustomerContact>,
+);
+
+// Active reservations of any room whose stay overlaps the given dates.
+// Check out dates are exclusive, so a stay may start on the day another ends.
+pub fn overlapping_reservations<'a>(
     new_check_in_date: NaiveDate,
     new_check_out_date: NaiveDate,
-    room_id: i32,
-    reservation_id: Option<i32>,
-    conn: &mut PgConnection,
-) -> Result<i64, diesel::result::Error> {
-    let mut query = reservations::table.into_boxed();
-
-    // TODO: Changed logic because SQL Injection
-    query = query
-        .filter(reservations::room_id.eq(room_id))
-        .filter(sql::<Bool>(&format!(
-            "('{new_check_in_date}' BETWEEN check_in_date AND check_out_date) OR
-    ('{new_check_out_date}' BETWEEN check_in_date AND check_out_date) OR
-    (check_in_date BETWEEN '{new_check_in_date}' AND '{new_check_out_date}') OR
-    (check_out_date BETWEEN '{new_check_in_date}' AND '{new_check_out_date}')
-    ",
-            new_check_in_date = new_check_in_date,
-            new_check_out_date = new_check_out_date,
-        )));
+) -> reservations::BoxedQuery<'a, Pg> {
+    reservations::table
+        .into_boxed()
+        .filter(reservations::status.eq_any(ReservationStatus::HOLDING_INVENTORY))
+        .filter(reservations::check_in_date.lt(new_check_out_date))
+        .filter(reservations::check_out_date.gt(new_check_in_date))
+}
 
-    if let Some(id) = reservation_id {
-        query = query.filter(reservations::id.ne(id));
-    }
+// Exclusion constraint keeping active stays of the same room from overlapping
+const NO_OVERLAPPING_STAYS_CONSTRAINT: &str = "reservations_no_overlapping_stays";
 
-    query.count().get_result::<i64>(conn)
+fn map_overlapping_stays(err: diesel::result::Error) -> AppError {
+    match &err {
+        diesel::result::Error::DatabaseError(_, info)
+            if info.constraint_name() == Some(NO_OVERLAPPING_STAYS_CONSTRAINT) =>
+        {
+            AppError::Conflict(
+                "Dates unavailable, the room is already booked for these dates.".to_string(),
+            )
+        }
+        _ => AppError::DatabaseError(err),
+    }
 }
 
 pub fn create_reservation(
@@ -48,69 +59,65 @@ pub fn create_reservation(
 ) -> Result<(), AppError> {
     let now = Utc::now();
 
-    let overlapping_count = check_overlapping(
-        data.check_in_date,
-        data.check_out_date,
-        data.room_id,
-        None,
-        conn,
-    )?;
-
-    if overlapping_count > 0 {
+    if !data.status.is_initial() {
         return Err(AppError::BadRequest(
-            "Dates overlap with an existing reservation.".to_string(),
+            "New reservations must be pending or confirmed.".to_string(),
         ));
     }
 
-    let new_customer_contact = NewCustomerContact {
-        full_name: &data.full_name,
-        email: &data.email,
-        phone_number: &data.phone_number,
-        created_at: &now,
-        updated_at: &now,
-    };
+    conn.transaction::<_, AppError, _>(|conn| {
+        let room: RoomWithType = rooms::table
+            .filter(rooms::id.eq(data.room_id))
+            .inner_join(room_types::table)
+            .select((rooms::id, room_types::price_per_night))
+            .first::<RoomWithType>(conn)
+            .or_not_found("Room not found.")?;
 
-    let room: RoomWithType = rooms::table
-        .filter(rooms::id.eq(data.room_id))
-        .inner_join(room_types::table)
-        .select((rooms::id, room_types::price_per_night))
-        .first::<RoomWithType>(conn)?;
-
-    let customer_contact_data = diesel::insert_into(customer_contacts::table)
-        .values(&new_customer_contact)
-        .get_result::<CustomerContact>(conn)?;
-    let diff = data
-        .check_out_date
-        .signed_duration_since(data.check_in_date);
-    let total_price = (diff.num_days() as i32) * room.price_per_night;
-
-    let mut new_reservation = NewReservation {
-        room_id: &data.room_id,
-        customer_contact_id: customer_contact_data.id,
-        check_in_date: &data.check_in_date,
-        check_out_date: &data.check_out_date,
-        total_price,
-        status: &data.status,
-        created_by: Some(staff_id),
-        created_at: &now,
-        updated_by: Some(staff_id),
-        updated_at: &now,
-        confirmed_by: None,
-        confirmed_at: None,
-        cancelled_by: None,
-        cancelled_at: None,
-    };
+        let customer_contact_id = resolve_customer_contact(conn, data)?;
+        let diff = data
+            .check_out_date
+            .signed_duration_since(data.check_in_date);
+        let total_price = (diff.num_days() as i32) * room.price_per_night;
 
-    if data.status.as_str() == "confirmed" {
-        new_reservation.confirmed_by = Some(staff_id);
-        new_reservation.confirmed_at = Some(now);
-    }
+        let mut new_reservation = NewReservation {
+            room_id: &data.room_id,
+            customer_contact_id,
+            check_in_date: &data.check_in_date,
+            check_out_date: &data.check_out_date,
+            total_price,
+            status: data.status,
+            created_by: Some(staff_id),
+            created_at: &now,
+            updated_by: Some(staff_id),
+            updated_at: &now,
+            confirmed_by: None,
+            confirmed_at: None,
+            cancelled_by: None,
+            cancelled_at: None,
+        };
+
+        if data.status == ReservationStatus::Confirmed {
+            new_reservation.confirmed_by = Some(staff_id);
+            new_reservation.confirmed_at = Some(now);
+        }
 
-    let _ = diesel::insert_into(reservations::table)
-        .values(&new_reservation)
-        .execute(conn)?;
+        let reservation = diesel::insert_into(reservations::table)
+            .values(&new_reservation)
+            .get_result::<Reservation>(conn)
+            .map_err(map_overlapping_stays)?;
 
-    Ok(())
+        record_audit_event(
+            conn,
+            Some(staff_id),
+            AuditEntity::Reservation,
+            reservation.id,
+            "create",
+            None,
+            Some(&reservation),
+        )?;
+
+        Ok(())
+    })
 }
 
 pub fn update_reservation_by_id(
@@ -118,95 +125,166 @@ pub fn update_reservation_by_id(
     reservation_id: i32,
     data: &CreateOrUpdateReservationRequest,
     staff_id: i32,
+) -> Result<(), AppError> {
+    conn.transaction::<_, AppError, _>(|conn| {
+        let reservation = reservations::table
+            .filter(reservations::id.eq(reservation_id))
+            .for_update()
+            .first::<Reservation>(conn)
+            .or_not_found("Reservation not found.")?;
+
+        if reservation.status != data.status && !reservation.status.can_transition_to(data.status) {
+            return Err(AppError::Conflict(format!(
+                "Cannot change reservation status from {} to {}.",
+                reservation.status, data.status
+            )));
+        }
+
+        let room: RoomWithType = rooms::table
+            .filter(rooms::id.eq(data.room_id))
+            .inner_join(room_types::table)
+            .select((rooms::id, room_types::price_per_night))
+            .first::<RoomWithType>(conn)
+            .or_not_found("Room not found.")?;
+        let now = Utc::now();
+
+        let customer_contact_id = resolve_customer_contact(conn, data)?;
+
+        // Calculate total price
+        let diff = data
+            .check_out_date
+            .signed_duration_since(data.check_in_date);
+        let total_price = (diff.num_days() as i32) * room.price_per_night;
+
+        // Update reservation
+        let update_reservation = UpdateReservation {
+            room_id: &data.room_id,
+            customer_contact_id,
+            check_in_date: &data.check_in_date,
+            check_out_date: &data.check_out_date,
+            total_price,
+            updated_by: Some(staff_id),
+            updated_at: &now,
+        };
+
+        // Stamp the lifecycle columns only when the status actually changes
+        let update_status = (reservation.status != data.status)
+            .then(|| UpdateReservationStatus::new(data.status, staff_id, now));
+
+        let updated_reservation =
+            diesel::update(reservations::table.filter(reservations::id.eq(reservation_id)))
+                .set((update_reservation, update_status))
+                .get_result::<Reservation>(conn)
+                .map_err(map_overlapping_stays)?;
+
+        record_audit_event(
+            conn,
+            Some(staff_id),
+            AuditEntity::Reservation,
+            reservation_id,
+            "update",
+            Some(&reservation),
+            Some(&updated_reservation),
+        )?;
+
+        Ok(())
+    })
+}
+
+pub fn update_reservation_status_by_id(
+    conn: &mut PgConnection,
+    reservation_id: i32,
+    status: ReservationStatus,
+    cancellation_reason: Option<String>,
+    staff_id: i32,
 ) -> Result<(), AppError> {
     let reservation = reservations::table
         .filter(reservations::id.eq(reservation_id))
-        .first::<Reservation>(conn)?;
-    let room: RoomWithType = rooms::table
-        .filter(rooms::id.eq(data.room_id))
-        .inner_join(room_types::table)
-        .select((rooms::id, room_types::price_per_night))
-        .first::<RoomWithType>(conn)?;
-    let now = Utc::now();
+        .first::<Reservation>(conn)
+        .or_not_found("Reservation not found.")?;
 
-    // Check check in or check out date is overlapping
-    let overlapping_count = check_overlapping(
-        data.check_in_date,
-        data.check_out_date,
-        data.room_id,
-        Some(reservation_id),
-        conn,
-    )?;
-    if overlapping_count > 0 {
-        return Err(AppError::BadRequest(
-            "Dates overlap with an existing reservation.".to_string(),
-        ));
+    if !reservation.status.can_transition_to(status) {
+        return Err(AppError::Conflict(format!(
+            "Cannot change reservation status from {} to {}.",
+            reservation.status, status
+        )));
     }
 
-    // Update customer contact
-    let update_customer_contact = UpdateCustomerContact {
-        full_name: &data.full_name,
-        email: &data.email,
-        phone_number: &data.phone_number,
-        updated_at: &now,
-    };
-    diesel::update(
-        customer_contacts::table.filter(customer_contacts::id.eq(&reservation.customer_contact_id)),
-    )
-    .set(update_customer_contact)
-    .execute(conn)?;
-
-    // Calculate total price
-    let diff = data
-        .check_out_date
-        .signed_duration_since(data.check_in_date);
-    let total_price = (diff.num_days() as i32) * room.price_per_night;
-
-    // Update reservation
-    let mut update_reservation = UpdateReservation {
-        room_id: &data.room_id,
-        check_in_date: &data.check_in_date,
-        check_out_date: &data.check_out_date,
-        total_price,
-        status: &data.status,
-        updated_by: Some(staff_id),
-        updated_at: &now,
-        confirmed_by: None,
-        confirmed_at: None,
-        cancelled_by: None,
-        cancelled_at: None,
-    };
+    let now = Utc::now();
+    let mut update_status = UpdateReservationStatus::new(status, staff_id, now);
+    update_status.cancellation_reason = cancellation_reason;
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        // Only update if nobody changed the status since we read it
+        let updated_reservation = diesel::update(
+            reservations::table
+                .filter(reservations::id.eq(reservation_id))
+                .filter(reservations::status.eq(reservation.status)),
+        )
+        .set((
+            update_status,
+            reservations::updated_by.eq(Some(staff_id)),
+            reservations::updated_at.eq(now),
+        ))
+        .get_result::<Reservation>(conn)
+        .optional()?
+        .ok_or_else(|| {
+            AppError::Conflict("Reservation status was changed by another request.".to_string())
+        })?;
+
+        record_audit_event(
+            conn,
+            Some(staff_id),
+            AuditEntity::Reservation,
+            reservation_id,
+            status.as_str(),
+            Some(&reservation),
+            Some(&updated_reservation),
+        )?;
+
+        Ok(())
+    })
+}
 
-    if data.status.as_str() == "confirmed" {
-        update_reservation.confirmed_by = Some(staff_id);
-        update_reservation.confirmed_at = Some(now);
-    } else if data.status.as_str() == "cancelled" {
-        update_reservation.cancelled_by = Some(staff_id);
-        update_reservation.cancelled_at = Some(now);
+fn to_reservation_with_join(
+    (reservation, room, room_type, customer_contact): ReservationJoinRow,
+) -> ReservationWithJoin {
+    ReservationWithJoin {
+        reservation,
+        room,
+        room_type,
+        customer_contact,
     }
+}
 
-    diesel::update(reservations::table.filter(reservations::id.eq(reservation_id)))
-        .set(update_reservation)
-        .execute(conn)?;
+pub fn get_reservation_by_id(
+    conn: &mut PgConnection,
+    reservation_id: i32,
+) -> Result<ReservationWithJoin, AppError> {
+    let result = reservations::table
+        .left_join(rooms::table.on(rooms::id.eq(reservations::room_id)))
+        .left_join(room_types::table.on(room_types::id.nullable().eq(rooms::type_id.nullable())))
+        .left_join(
+            customer_contacts::table
+                .on(customer_contacts::id.eq(reservations::customer_contact_id)),
+        )
+        .filter(reservations::id.eq(reservation_id))
+        .first::<ReservationJoinRow>(conn)
+        .or_not_found("Reservation not found.")?;
 
-    Ok(())
+    Ok(to_reservation_with_join(result))
 }
 
 pub fn get_reservations_with_pagination(
     conn: &mut PgConnection,
     page: i64,
     page_size: i64,
-) -> Result<(Vec<ReservationWithJoin>, PaginationMeta), diesel::result::Error> {
+) -> Result<(Vec<ReservationWithJoin>, PaginationMeta), AppError> {
     let total_items = reservations::table.count().get_result::<i64>(conn)?;
     let total_pages = (total_items as f64 / page_size as f64).ceil() as i64;
     let offset = (page - 1) * page_size;
 
-    let results: Vec<(
-        Reservation,
-        Option<Room>,
-        Option<RoomTypes>,
-        Option<CustomerContact>,
-    )> = reservations::table
+    let results: Vec<ReservationJoinRow> = reservations::table
         .left_join(rooms::table.on(rooms::id.eq(reservations::room_id)))
         .left_join(room_types::table.on(room_types::id.nullable().eq(rooms::type_id.nullable())))
         .left_join(
@@ -215,25 +293,11 @@ pub fn get_reservations_with_pagination(
         )
         .limit(page_size)
         .offset(offset)
-        .load::<(
-            Reservation,
-            Option<Room>,
-            Option<RoomTypes>,
-            Option<CustomerContact>,
-        )>(conn)?;
+        .load::<ReservationJoinRow>(conn)?;
     // let reservations_data = reservations::table.limit(page_size).offset(offset).load::<Reservation>(conn)?;
 
-    let formatted_results: Vec<ReservationWithJoin> = results
-        .into_iter()
-        .map(
-            |(reservation, room, room_type, customer_contact)| ReservationWithJoin {
-                reservation,
-                room,
-                room_type,
-                customer_contact,
-            },
-        )
-        .collect();
+    let formatted_results: Vec<ReservationWithJoin> =
+        results.into_iter().map(to_reservation_with_join).collect();
     let pagination_meta = PaginationMeta {
         total_items,
         total_pages,
@@ -243,3 +307,137 @@ pub fn get_reservations_with_pagination(
 
     Ok((formatted_results, pagination_meta))
 }
+
+// Longest window the calendar can be asked for in one request
+const MAX_CALENDAR_DAYS: i64 = 93;
+
+pub fn get_reservation_calendar(
+    conn: &mut PgConnection,
+    params: &CalendarParams,
+) -> Result<Vec<CalendarRoom>, AppError> {
+    let days = params.to.signed_duration_since(params.from).num_days() + 1;
+
+    if days < 1 {
+        return Err(AppError::BadRequest(
+            "to must not be before from.".to_string(),
+        ));
+    }
+    if days > MAX_CALENDAR_DAYS {
+        return Err(AppError::BadRequest(format!(
+            "Calendar range cannot exceed {} days.",
+            MAX_CALENDAR_DAYS
+        )));
+    }
+
+    let rooms_data: Vec<(Room, Option<RoomTypes>)> = rooms::table
+        .left_join(room_types::table.on(room_types::id.nullable().eq(rooms::type_id.nullable())))
+        .order(rooms::room_name)
+        .load::<(Room, Option<RoomTypes>)>(conn)?;
+
+    // A reservation occupies every night from check in up to the night before check out
+    let reservations_data: Vec<(i32, CalendarReservation)> = reservations::table
+        .inner_join(
+            customer_contacts::table
+                .on(customer_contacts::id.eq(reservations::customer_contact_id)),
+        )
+        .filter(reservations::check_in_date.le(params.to))
+        .filter(reservations::check_out_date.gt(params.from))
+        .filter(reservations::status.eq_any(ReservationStatus::OCCUPYING_NIGHTS))
+        .order(reservations::check_in_date)
+        .select((
+            reservations::room_id,
+            (
+                reservations::id,
+                reservations::status,
+                reservations::check_in_date,
+                reservations::check_out_date,
+                customer_contacts::full_name,
+            ),
+        ))
+        .load::<(i32, CalendarReservation)>(conn)?;
+
+    let mut reservations_by_room: HashMap<i32, Vec<CalendarReservation>> = HashMap::new();
+    for (room_id, reservation) in reservations_data {
+        reservations_by_room
+            .entry(room_id)
+            .or_default()
+            .push(reservation);
+    }
+
+    let calendar = rooms_data
+        .into_iter()
+        .map(|(room, room_type)| {
+            let room_reservations = reservations_by_room.remove(&room.id).unwrap_or_default();
+            let days = params
+                .from
+                .iter_days()
+                .take(days as usize)
+                .map(|date| CalendarDay {
+                    date,
+                    reservations: room_reservations
+                        .iter()
+                        .filter(|reservation| {
+                            reservation.check_in_date <= date && date < reservation.check_out_date
+                        })
+                        .cloned()
+                        .collect(),
+                })
+                .collect();
+
+            CalendarRoom {
+                room,
+                room_type,
+                days,
+            }
+        })
+        .collect();
+
+    Ok(calendar)
+}
+
+// Total rooms and rooms with a guest currently checked in
+pub fn get_room_occupancy(conn: &mut PgConnection) -> Result<(i64, i64), AppError> {
+    let total_rooms = rooms::table.count().get_result::<i64>(conn)?;
+    let occupied_rooms = reservations::table
+        .filter(reservations::status.eq(ReservationStatus::CheckedIn))
+        .select(diesel::dsl::count(reservations::room_id).aggregate_distinct())
+        .get_result::<i64>(conn)?;
+
+    Ok((total_rooms, occupied_rooms))
+}
+
+// Deletes reservations cancelled before `cutoff`, returning how many went.
+// Rows cancelled before cancelled_at existed fall back to updated_at.
+pub fn purge_cancelled_reservations(
+    conn: &mut PgConnection,
+    cutoff: DateTime<Utc>,
+) -> Result<usize, AppError> {
+    conn.transaction::<_, AppError, _>(|conn| {
+        let purged_reservations = diesel::delete(
+            reservations::table
+                .filter(reservations::status.eq(ReservationStatus::Cancelled))
+                .filter(
+                    reservations::cancelled_at
+                        .lt(cutoff)
+                        .or(reservations::cancelled_at
+                            .is_null()
+                            .and(reservations::updated_at.lt(cutoff))),
+                ),
+        )
+        .get_results::<Reservation>(conn)?;
+
+        for reservation in &purged_reservations {
+            record_audit_event(
+                conn,
+                None,
+                AuditEntity::Reservation,
+                reservation.id,
+                "purge",
+                Some(reservation),
+                None,
+            )?;
+        }
+
+        Ok(purged_reservations.len())
+    })
+}
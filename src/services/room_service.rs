@@ -1,18 +1,25 @@
+use crate::models::audit_event::AuditEntity;
 use crate::models::room::{
-    CreateOrUpdateRoomTypesRequest, CreateRoomRequest, NewRoom, NewRoomTypes, Room, RoomTypes,
-    UpdateRoomData, UpdateRoomRequest, UpdateRoomTypeData,
+    AvailableRoomType, CreateOrUpdateRoomTypesRequest, CreateRoomRequest, NewRoom, NewRoomTypes,
+    Room, RoomAvailabilityParams, RoomTypeSummary, RoomTypeWithRooms, RoomTypes, UpdateRoomData,
+    UpdateRoomRequest, UpdateRoomTypeData,
 };
-use crate::schema::room_types;
 use crate::schema::rooms::dsl::*;
+use crate::schema::{reservations, room_types};
+use crate::services::audit_service::record_audit_event;
+use crate::services::reservation_service::overlapping_reservations;
+use crate::utils::common::{AppError, DieselResultExt};
 use crate::utils::response::PaginationMeta;
-use chrono::Utc;
+use chrono::{Duration, Utc};
+use diesel::dsl::count_star;
 use diesel::prelude::*;
+use std::collections::HashMap;
 
 pub fn create_room(
     conn: &mut PgConnection,
     data: &CreateRoomRequest,
     staff_id: i32,
-) -> Result<(), diesel::result::Error> {
+) -> Result<(), AppError> {
     let now = Utc::now();
 
     let new_room = NewRoom {
@@ -26,9 +33,22 @@ pub fn create_room(
         updated_by: staff_id,
     };
 
-    let _ = diesel::insert_into(rooms).values(&new_room).execute(conn)?;
+    conn.transaction(|conn| {
+        let room = diesel::insert_into(rooms)
+            .values(&new_room)
+            .get_result::<Room>(conn)
+            .or_conflict("Room name already exists.")?;
 
-    Ok(())
+        record_audit_event(
+            conn,
+            Some(staff_id),
+            AuditEntity::Room,
+            room.id,
+            "create",
+            None,
+            Some(&room),
+        )
+    })
 }
 
 pub fn update_room_by_id(
@@ -36,8 +56,11 @@ pub fn update_room_by_id(
     room_id: i32,
     data: &UpdateRoomRequest,
     staff_id: i32,
-) -> Result<(), diesel::result::Error> {
-    rooms.filter(id.eq(&room_id)).first::<Room>(conn)?;
+) -> Result<(), AppError> {
+    let room = rooms
+        .filter(id.eq(&room_id))
+        .first::<Room>(conn)
+        .or_not_found("Room not found.")?;
 
     let now = Utc::now();
     let duplicate_name = rooms
@@ -47,33 +70,41 @@ pub fn update_room_by_id(
         .optional()?;
 
     if duplicate_name.is_some() {
-        return Err(diesel::result::Error::DatabaseError(
-            diesel::result::DatabaseErrorKind::UniqueViolation,
-            Box::new("Room name already exists".to_string()),
-        ));
+        return Err(AppError::Conflict("Room name already exists.".to_string()));
     }
 
     let updated_data = UpdateRoomData {
         room_name: data.room_name.clone(),
         capacity: data.capacity,
-        is_available: data.is_available.clone(),
+        is_available: data.is_available,
         type_id: data.type_id,
         updated_at: now,
         updated_by: staff_id,
     };
 
-    diesel::update(rooms.filter(id.eq(room_id)))
-        .set(updated_data)
-        .execute(conn)?;
+    conn.transaction(|conn| {
+        let updated_room = diesel::update(rooms.filter(id.eq(room_id)))
+            .set(updated_data)
+            .get_result::<Room>(conn)
+            .or_conflict("Room name already exists.")?;
 
-    Ok(())
+        record_audit_event(
+            conn,
+            Some(staff_id),
+            AuditEntity::Room,
+            room_id,
+            "update",
+            Some(&room),
+            Some(&updated_room),
+        )
+    })
 }
 
 pub fn get_rooms_with_pagination(
     conn: &mut PgConnection,
     page: i64,
     page_size: i64,
-) -> Result<(Vec<Room>, PaginationMeta), diesel::result::Error> {
+) -> Result<(Vec<Room>, PaginationMeta), AppError> {
     let total_items = rooms.count().get_result::<i64>(conn)?;
     let total_pages = (total_items as f64 / page_size as f64).ceil() as i64;
 
@@ -90,11 +121,100 @@ pub fn get_rooms_with_pagination(
     Ok((rooms_data, pagination_meta))
 }
 
+pub fn get_room_by_id(conn: &mut PgConnection, room_id: i32) -> Result<Room, AppError> {
+    rooms
+        .filter(id.eq(room_id))
+        .first::<Room>(conn)
+        .or_not_found("Room not found.")
+}
+
+pub fn get_room_types_with_pagination(
+    conn: &mut PgConnection,
+    page: i64,
+    page_size: i64,
+) -> Result<(Vec<RoomTypeSummary>, PaginationMeta), AppError> {
+    let total_items = room_types::table.count().get_result::<i64>(conn)?;
+    let total_pages = (total_items as f64 / page_size as f64).ceil() as i64;
+
+    let offset = (page - 1) * page_size;
+    let room_types_data = room_types::table
+        .order(room_types::type_name.asc())
+        .limit(page_size)
+        .offset(offset)
+        .load::<RoomTypes>(conn)?;
+    let room_type_ids: Vec<i32> = room_types_data
+        .iter()
+        .map(|room_type| room_type.id)
+        .collect();
+
+    let room_counts: HashMap<Option<i32>, i64> = rooms
+        .filter(type_id.eq_any(&room_type_ids))
+        .group_by(type_id)
+        .select((type_id, count_star()))
+        .load::<(Option<i32>, i64)>(conn)?
+        .into_iter()
+        .collect();
+
+    let today = Utc::now().date_naive();
+    let booked_room_ids =
+        overlapping_reservations(today, today + Duration::days(1)).select(reservations::room_id);
+    let available_room_counts: HashMap<Option<i32>, i64> = rooms
+        .filter(type_id.eq_any(&room_type_ids))
+        .filter(is_available.eq(true))
+        .filter(id.ne_all(booked_room_ids))
+        .group_by(type_id)
+        .select((type_id, count_star()))
+        .load::<(Option<i32>, i64)>(conn)?
+        .into_iter()
+        .collect();
+
+    let summaries = room_types_data
+        .into_iter()
+        .map(|room_type| RoomTypeSummary {
+            room_count: room_counts.get(&Some(room_type.id)).copied().unwrap_or(0),
+            available_room_count: available_room_counts
+                .get(&Some(room_type.id))
+                .copied()
+                .unwrap_or(0),
+            room_type,
+        })
+        .collect();
+
+    let pagination_meta = PaginationMeta {
+        total_items,
+        total_pages,
+        current_page: page,
+        page_size,
+    };
+
+    Ok((summaries, pagination_meta))
+}
+
+pub fn get_room_type_by_id(
+    conn: &mut PgConnection,
+    room_type_id: i32,
+) -> Result<RoomTypeWithRooms, AppError> {
+    let room_type = room_types::table
+        .filter(room_types::id.eq(room_type_id))
+        .first::<RoomTypes>(conn)
+        .or_not_found("Room type not found.")?;
+
+    let rooms_data = rooms
+        .filter(type_id.eq(room_type_id))
+        .order(room_name.asc())
+        .load::<Room>(conn)?;
+
+    Ok(RoomTypeWithRooms {
+        room_type,
+        rooms: rooms_data,
+    })
+}
+
 pub fn create_room_type(
     conn: &mut PgConnection,
     new_room_types: &CreateOrUpdateRoomTypesRequest,
     staff_id: i32,
-) -> Result<(), diesel::result::Error> {
+) -> Result<(), AppError> {
     let now = Utc::now();
 
     let create_data = NewRoomTypes {
@@ -107,18 +227,28 @@ pub fn create_room_type(
         updated_by: Some(staff_id),
     };
 
-    let room_types_data = diesel::insert_into(room_types::table)
-        .values(&create_data)
-        .get_result::<RoomTypes>(conn)?;
+    conn.transaction(|conn| {
+        let room_types_data = diesel::insert_into(room_types::table)
+            .values(&create_data)
+            .get_result::<RoomTypes>(conn)
+            .or_conflict("Room type name already exists.")?;
 
-    if let Some(room_ids) = &new_room_types.room_ids {
-        let _ = diesel::update(rooms)
-            .filter(id.eq_any(room_ids))
-            .set(type_id.eq(room_types_data.id))
-            .execute(conn)?;
-    }
+        record_audit_event(
+            conn,
+            Some(staff_id),
+            AuditEntity::RoomType,
+            room_types_data.id,
+            "create",
+            None,
+            Some(&room_types_data),
+        )?;
 
-    Ok(())
+        if let Some(room_ids) = &new_room_types.room_ids {
+            assign_rooms_to_type(conn, room_ids, room_types_data.id, staff_id)?;
+        }
+
+        Ok(())
+    })
 }
 
 pub fn update_room_type_by_id(
@@ -126,7 +256,7 @@ pub fn update_room_type_by_id(
     room_type_id: i32,
     data: &CreateOrUpdateRoomTypesRequest,
     staff_id: i32,
-) -> Result<(), diesel::result::Error> {
+) -> Result<(), AppError> {
     let now = Utc::now();
 
     let updated_data = UpdateRoomTypeData {
@@ -137,16 +267,129 @@ pub fn update_room_type_by_id(
         updated_by: staff_id,
     };
 
-    diesel::update(room_types::table.filter(room_types::id.eq(room_type_id)))
-        .set(updated_data)
-        .execute(conn)?;
+    conn.transaction(|conn| {
+        let room_type = room_types::table
+            .find(room_type_id)
+            .for_update()
+            .first::<RoomTypes>(conn)
+            .or_not_found("Room type not found.")?;
+
+        let updated_room_type =
+            diesel::update(room_types::table.filter(room_types::id.eq(room_type_id)))
+                .set(updated_data)
+                .get_result::<RoomTypes>(conn)
+                .or_conflict("Room type name already exists.")?;
+
+        record_audit_event(
+            conn,
+            Some(staff_id),
+            AuditEntity::RoomType,
+            room_type_id,
+            "update",
+            Some(&room_type),
+            Some(&updated_room_type),
+        )?;
+
+        if let Some(room_ids) = &data.room_ids {
+            assign_rooms_to_type(conn, room_ids, room_type_id, staff_id)?;
+        }
+
+        Ok(())
+    })
+}
+
+// Moves rooms to a room type, leaving an audit event on every room that moved
+fn assign_rooms_to_type(
+    conn: &mut PgConnection,
+    room_ids: &[i32],
+    room_type_id: i32,
+    staff_id: i32,
+) -> Result<(), AppError> {
+    let mut requested_ids = room_ids.to_vec();
+    requested_ids.sort_unstable();
+    requested_ids.dedup();
+
+    let found_count = rooms
+        .filter(id.eq_any(&requested_ids))
+        .count()
+        .get_result::<i64>(conn)?;
+    if found_count != requested_ids.len() as i64 {
+        return Err(AppError::BadRequest("Some rooms were not found.".to_string()));
+    }
+
+    let rooms_data = rooms
+        .filter(id.eq_any(room_ids))
+        .filter(type_id.is_distinct_from(room_type_id))
+        .for_update()
+        .load::<Room>(conn)?;
 
-    if let Some(room_ids) = &data.room_ids {
-        let _ = diesel::update(rooms)
-            .filter(id.eq_any(room_ids))
+    for room in rooms_data {
+        let updated_room = diesel::update(rooms.filter(id.eq(room.id)))
             .set(type_id.eq(room_type_id))
-            .execute(conn)?;
+            .get_result::<Room>(conn)?;
+
+        record_audit_event(
+            conn,
+            Some(staff_id),
+            AuditEntity::Room,
+            room.id,
+            "update",
+            Some(&room),
+            Some(&updated_room),
+        )?;
     }
 
     Ok(())
 }
+
+pub fn search_available_rooms(
+    conn: &mut PgConnection,
+    params: &RoomAvailabilityParams,
+) -> Result<Vec<AvailableRoomType>, AppError> {
+    if params.check_out <= params.check_in {
+        return Err(AppError::BadRequest(
+            "check_out must be after check_in.".to_string(),
+        ));
+    }
+
+    let booked_room_ids =
+        overlapping_reservations(params.check_in, params.check_out).select(reservations::room_id);
+
+    let mut query = rooms
+        .inner_join(room_types::table)
+        .filter(is_available.eq(true))
+        .filter(id.ne_all(booked_room_ids))
+        .into_boxed();
+
+    if let Some(guests) = params.guests {
+        query = query.filter(capacity.ge(guests));
+    }
+
+    if let Some(room_type_id) = params.type_id {
+        query = query.filter(type_id.eq(room_type_id));
+    }
+
+    let results = query
+        .order((room_types::id, room_name))
+        .load::<(Room, RoomTypes)>(conn)?;
+
+    let nights = params
+        .check_out
+        .signed_duration_since(params.check_in)
+        .num_days();
+    let mut available: Vec<AvailableRoomType> = Vec::new();
+
+    for (room, room_type) in results {
+        match available.last_mut() {
+            Some(group) if group.room_type.id == room_type.id => group.rooms.push(room),
+            _ => available.push(AvailableRoomType {
+                total_price: (nights as i32) * room_type.price_per_night,
+                nights,
+                room_type,
+                rooms: vec![room],
+            }),
+        }
+    }
+
+    Ok(available)
+}
@@ -1,42 +1,140 @@
-use crate::config::auth::staff_jwt_secret;
+use crate::config::app::JwtConfig;
+use crate::config::auth::{
+    LOGIN_ATTEMPT_WINDOW_MINUTES, MAX_FAILED_LOGIN_ATTEMPTS, MAX_LOGIN_LOCKOUT_MINUTES,
+    PASSWORD_RESET_TTL_MINUTES,
+};
+use crate::models::audit_event::AuditEntity;
 use crate::models::jwt::Claims;
+use crate::models::login_ip_attempt::{LoginIpAttempt, NewLoginIpAttempt};
 use crate::models::staff::{
-    CreateStaffRequest, NewStaff, Staff, UpdateStaffData, UpdateStaffRequest,
+    ChangePasswordRequest, CreateStaffRequest, NewStaff, Role, Staff, UpdateStaffData,
+    UpdateStaffRequest,
 };
+use crate::models::staff_password_reset::NewStaffPasswordReset;
+use crate::models::staff_session::{AuthTokens, NewStaffSession};
 use crate::schema::staff::dsl::*;
+use crate::schema::{login_ip_attempts, staff_password_resets, staff_sessions};
+use crate::services::audit_service::record_audit_event;
+use crate::utils::common::{AppError, DieselResultExt};
 use bcrypt::{hash, verify, DEFAULT_COST};
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use diesel::prelude::*;
 use jsonwebtoken::{encode, EncodingKey, Header};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
 
 pub fn create_staff(
     conn: &mut PgConnection,
     new_staff: &CreateStaffRequest,
-) -> Result<(), diesel::result::Error> {
+    actor_id: i32,
+    actor_role: Role,
+) -> Result<(), AppError> {
+    if !actor_role.can_manage(new_staff.position) {
+        return Err(AppError::Forbidden(
+            "Only admins can create admin accounts.".to_string(),
+        ));
+    }
+
     let now = Utc::now();
     let hashed_password = hash(&new_staff.password, DEFAULT_COST).expect("Failed to hash password");
     let new_staff = NewStaff {
         name: &new_staff.name,
         email: &new_staff.email,
         password: &hashed_password,
-        position: &new_staff.position,
+        position: new_staff.position.as_str(),
+        // Accounts created for someone else start with a temporary password
+        must_change_password: true,
         created_at: &now,
         updated_at: &now,
     };
 
-    diesel::insert_into(staff)
-        .values(&new_staff)
-        .execute(conn)?;
+    conn.transaction::<_, AppError, _>(|conn| {
+        let staff_data = diesel::insert_into(staff)
+            .values(&new_staff)
+            .get_result::<Staff>(conn)
+            .or_conflict("Staff email already exists.")?;
 
-    Ok(())
+        record_audit_event(
+            conn,
+            Some(actor_id),
+            AuditEntity::Staff,
+            staff_data.id,
+            "create",
+            None,
+            Some(&staff_data),
+        )?;
+
+        Ok(())
+    })
+}
+
+// Bootstraps an admin account from the admin CLI, where there is no signed
+// in staff member to act as. The chosen password is kept as is.
+pub fn create_admin_staff(
+    conn: &mut PgConnection,
+    name_input: &str,
+    email_input: &str,
+    password_input: &str,
+) -> Result<Staff, AppError> {
+    let now = Utc::now();
+    let hashed_password = hash(password_input, DEFAULT_COST).expect("Failed to hash password");
+    let new_staff = NewStaff {
+        name: name_input,
+        email: email_input,
+        password: &hashed_password,
+        position: Role::Admin.as_str(),
+        must_change_password: false,
+        created_at: &now,
+        updated_at: &now,
+    };
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        let staff_data = diesel::insert_into(staff)
+            .values(&new_staff)
+            .get_result::<Staff>(conn)
+            .or_conflict("Staff email already exists.")?;
+
+        record_audit_event(
+            conn,
+            None,
+            AuditEntity::Staff,
+            staff_data.id,
+            "create",
+            None,
+            Some(&staff_data),
+        )?;
+
+        Ok(staff_data)
+    })
+}
+
+pub fn get_staff_by_id(conn: &mut PgConnection, staff_id: i32) -> Result<Staff, AppError> {
+    staff
+        .filter(id.eq(staff_id))
+        .first::<Staff>(conn)
+        .or_not_found("Staff not found.")
 }
 
 pub fn update_staff_by_id(
     conn: &mut PgConnection,
     staff_id: i32,
     data: &mut UpdateStaffRequest,
-) -> Result<(), diesel::result::Error> {
-    staff.filter(id.eq(&staff_id)).first::<Staff>(conn)?;
+    actor_id: i32,
+    actor_role: Role,
+) -> Result<(), AppError> {
+    let staff_data = staff
+        .filter(id.eq(&staff_id))
+        .first::<Staff>(conn)
+        .or_not_found("Staff not found.")?;
+
+    if !actor_role.can_manage(Role::from_position(&staff_data.position))
+        || !actor_role.can_manage(data.position)
+    {
+        return Err(AppError::Forbidden(
+            "Only admins can manage admin accounts.".to_string(),
+        ));
+    }
 
     let now = Utc::now();
 
@@ -46,55 +144,682 @@ pub fn update_staff_by_id(
 
     let updated_data = UpdateStaffData {
         name: data.name.clone(),
-        position: data.position.clone(),
+        position: data.position.as_str().to_string(),
+        is_active: data.is_active,
+        must_change_password: data.password.is_some().then_some(true),
         updated_at: now,
         password: data.password.clone(),
     };
 
-    diesel::update(staff.filter(id.eq(staff_id)))
-        .set(updated_data)
+    conn.transaction::<_, AppError, _>(|conn| {
+        let updated_staff = diesel::update(staff.filter(id.eq(staff_id)))
+            .set(updated_data)
+            .get_result::<Staff>(conn)?;
+
+        record_audit_event(
+            conn,
+            Some(actor_id),
+            AuditEntity::Staff,
+            staff_id,
+            "update",
+            Some(&staff_data),
+            Some(&updated_staff),
+        )?;
+
+        // Deactivated staff lose every session straight away
+        if data.is_active == Some(false) {
+            revoke_staff_sessions(conn, staff_id)?;
+        }
+
+        Ok(())
+    })
+}
+
+// Same answer for unknown emails, wrong passwords, deactivated and locked
+// accounts so the login endpoint does not reveal which emails exist.
+const INVALID_CREDENTIALS: &str = "Invalid email or password.";
+const TOO_MANY_ATTEMPTS: &str = "Too many failed login attempts, try again later.";
+
+// A login is split in three so the bcrypt check between the lookup and the
+// bookkeeping runs without holding a pooled connection.
+pub struct LoginAttempt {
+    staff_data: Option<Staff>,
+    is_locked: bool,
+    is_valid: bool,
+}
+
+// ip_address is None when the client address is unknown, which skips the
+// per-IP lockout rather than lumping such clients together.
+pub fn start_login(
+    conn: &mut PgConnection,
+    email_input: &str,
+    ip_address: Option<&str>,
+) -> Result<LoginAttempt, AppError> {
+    let now = Utc::now();
+
+    if let Some(ip_address) = ip_address {
+        let ip_attempt = login_ip_attempts::table
+            .find(ip_address)
+            .select((
+                login_ip_attempts::failed_attempts,
+                login_ip_attempts::locked_until,
+                login_ip_attempts::updated_at,
+            ))
+            .first::<LoginIpAttempt>(conn)
+            .optional()?;
+
+        if ip_attempt
+            .as_ref()
+            .is_some_and(|attempt| attempt.locked_until.is_some_and(|until| until > now))
+        {
+            return Err(AppError::TooManyRequests(TOO_MANY_ATTEMPTS.to_string()));
+        }
+    }
+
+    let staff_data = staff
+        .filter(email.eq(email_input))
+        .first::<Staff>(conn)
+        .optional()?;
+    let is_locked = staff_data
+        .as_ref()
+        .is_some_and(|staff_data| staff_data.locked_until.is_some_and(|until| until > now));
+
+    Ok(LoginAttempt {
+        staff_data,
+        is_locked,
+        is_valid: false,
+    })
+}
+
+impl LoginAttempt {
+    // Unknown emails and locked accounts still pay for a bcrypt check so
+    // response times match, the dummy check never counts as a match
+    pub fn check_password(mut self, password_input: &str) -> Self {
+        self.is_valid = match &self.staff_data {
+            Some(staff_data) if !self.is_locked => {
+                verify(password_input, &staff_data.password).unwrap_or(false)
+            }
+            _ => {
+                let _ = verify(password_input, dummy_password_hash());
+                false
+            }
+        };
+        self
+    }
+}
+
+pub fn finish_login(
+    conn: &mut PgConnection,
+    attempt: LoginAttempt,
+    ip_address: Option<&str>,
+    jwt_config: &JwtConfig,
+) -> Result<(Staff, AuthTokens), AppError> {
+    let now = Utc::now();
+    let LoginAttempt {
+        staff_data,
+        is_locked,
+        is_valid,
+    } = attempt;
+
+    match staff_data {
+        Some(staff_data) if is_valid && !is_locked && staff_data.is_active => {
+            diesel::update(staff.filter(id.eq(staff_data.id)))
+                .set((
+                    failed_login_attempts.eq(0),
+                    locked_until.eq(None::<DateTime<Utc>>),
+                ))
+                .execute(conn)?;
+
+            let tokens = create_session(conn, &staff_data, jwt_config)?;
+
+            Ok((staff_data, tokens))
+        }
+        staff_data => {
+            // Attempts against a locked account neither count nor extend the lock
+            if let Some(staff_data) = staff_data.filter(|_| !is_locked) {
+                record_failed_staff_attempt(conn, staff_data.id, now)?;
+            }
+
+            if let Some(ip_address) = ip_address {
+                record_failed_ip_attempt(conn, ip_address, now)?;
+            }
+
+            Err(AppError::Unauthorized(INVALID_CREDENTIALS.to_string()))
+        }
+    }
+}
+
+// Counts are incremented in SQL so concurrent failures cannot overwrite each
+// other and slip under the limit.
+fn record_failed_staff_attempt(
+    conn: &mut PgConnection,
+    staff_id: i32,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    let attempts = diesel::update(staff.filter(id.eq(staff_id)))
+        .set(failed_login_attempts.eq(failed_login_attempts + 1))
+        .returning(failed_login_attempts)
+        .get_result::<i32>(conn)?;
+
+    if let Some(until) = lockout_until(attempts, now) {
+        diesel::update(
+            staff
+                .filter(id.eq(staff_id))
+                .filter(locked_until.is_null().or(locked_until.lt(until))),
+        )
+        .set(locked_until.eq(until))
+        .execute(conn)?;
+    }
+
+    Ok(())
+}
+
+fn record_failed_ip_attempt(
+    conn: &mut PgConnection,
+    ip_address: &str,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    // Failures older than the window are forgotten
+    let window_start = now - Duration::minutes(LOGIN_ATTEMPT_WINDOW_MINUTES);
+    diesel::update(
+        login_ip_attempts::table
+            .find(ip_address)
+            .filter(login_ip_attempts::updated_at.le(window_start)),
+    )
+    .set(login_ip_attempts::failed_attempts.eq(0))
+    .execute(conn)?;
+
+    let new_attempt = NewLoginIpAttempt {
+        ip_address,
+        failed_attempts: 1,
+        updated_at: &now,
+    };
+
+    let attempts = diesel::insert_into(login_ip_attempts::table)
+        .values(&new_attempt)
+        .on_conflict(login_ip_attempts::ip_address)
+        .do_update()
+        .set((
+            login_ip_attempts::failed_attempts.eq(login_ip_attempts::failed_attempts + 1),
+            login_ip_attempts::updated_at.eq(now),
+        ))
+        .returning(login_ip_attempts::failed_attempts)
+        .get_result::<i32>(conn)?;
+
+    if let Some(until) = lockout_until(attempts, now) {
+        diesel::update(
+            login_ip_attempts::table.find(ip_address).filter(
+                login_ip_attempts::locked_until
+                    .is_null()
+                    .or(login_ip_attempts::locked_until.lt(until)),
+            ),
+        )
+        .set(login_ip_attempts::locked_until.eq(until))
         .execute(conn)?;
+    }
 
     Ok(())
 }
 
-pub fn authenticate_staff(
+// Locks for one minute once the limit is reached and doubles with every
+// further failure, up to MAX_LOGIN_LOCKOUT_MINUTES.
+fn lockout_until(failed_attempts: i32, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if failed_attempts < MAX_FAILED_LOGIN_ATTEMPTS {
+        return None;
+    }
+
+    let exponent = (failed_attempts - MAX_FAILED_LOGIN_ATTEMPTS).min(16) as u32;
+    let minutes = 2i64.pow(exponent).min(MAX_LOGIN_LOCKOUT_MINUTES);
+
+    Some(now + Duration::minutes(minutes))
+}
+
+fn dummy_password_hash() -> &'static str {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+
+    DUMMY_HASH
+        .get_or_init(|| hash("dummy-password", DEFAULT_COST).expect("Failed to hash password"))
+}
+
+pub fn unlock_staff_by_id(
+    conn: &mut PgConnection,
+    staff_id: i32,
+    actor_id: i32,
+) -> Result<(), AppError> {
+    conn.transaction::<_, AppError, _>(|conn| {
+        let staff_data = staff
+            .filter(id.eq(staff_id))
+            .for_update()
+            .first::<Staff>(conn)
+            .or_not_found("Staff not found.")?;
+
+        let updated_staff = diesel::update(staff.filter(id.eq(staff_id)))
+            .set((
+                failed_login_attempts.eq(0),
+                locked_until.eq(None::<DateTime<Utc>>),
+                updated_at.eq(Utc::now()),
+            ))
+            .get_result::<Staff>(conn)?;
+
+        record_audit_event(
+            conn,
+            Some(actor_id),
+            AuditEntity::Staff,
+            staff_id,
+            "unlock",
+            Some(&staff_data),
+            Some(&updated_staff),
+        )?;
+
+        Ok(())
+    })
+}
+
+pub fn change_own_password(
+    conn: &mut PgConnection,
+    staff_id: i32,
+    session_id: i32,
+    data: &ChangePasswordRequest,
+) -> Result<(), AppError> {
+    let staff_data = staff
+        .filter(id.eq(staff_id))
+        .first::<Staff>(conn)
+        .or_not_found("Staff not found.")?;
+
+    if !verify(&data.current_password, &staff_data.password).unwrap_or(false) {
+        return Err(AppError::BadRequest(
+            "Current password is incorrect.".to_string(),
+        ));
+    }
+
+    let hashed_password = hash(&data.new_password, DEFAULT_COST).expect("Failed to hash password");
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        let updated_staff = diesel::update(staff.filter(id.eq(staff_id)))
+            .set((
+                password.eq(&hashed_password),
+                must_change_password.eq(false),
+                updated_at.eq(Utc::now()),
+            ))
+            .get_result::<Staff>(conn)?;
+
+        record_audit_event(
+            conn,
+            Some(staff_id),
+            AuditEntity::Staff,
+            staff_id,
+            "change_password",
+            Some(&staff_data),
+            Some(&updated_staff),
+        )?;
+
+        // Sign out every other device, the current one stays logged in
+        diesel::update(
+            staff_sessions::table
+                .filter(staff_sessions::staff_id.eq(staff_id))
+                .filter(staff_sessions::id.ne(session_id))
+                .filter(staff_sessions::revoked_at.is_null()),
+        )
+        .set(staff_sessions::revoked_at.eq(Utc::now()))
+        .execute(conn)?;
+
+        Ok(())
+    })
+}
+
+// Creates a one-time reset token for an active staff member. Returns None for
+// unknown emails so callers can answer the same way either way.
+pub fn request_password_reset(
     conn: &mut PgConnection,
     email_input: &str,
-    password_input: &str,
-) -> Result<(i32, String, String), String> {
+) -> Result<Option<String>, AppError> {
     let staff_data = staff
         .filter(email.eq(email_input))
+        .filter(is_active.eq(true))
         .first::<Staff>(conn)
-        .map_err(|_| "Staff not found.".to_string())?;
+        .optional()?;
 
-    if verify(password_input, &staff_data.password)
-        .map_err(|_| "Password verification failed.".to_string())?
-    {
-        let jwt = generate_jwt(staff_data.id)?;
+    let Some(staff_data) = staff_data else {
+        return Ok(None);
+    };
+
+    let now = Utc::now();
+    let token = generate_token();
+    let token_hash = hash_token(&token);
+    let expires_at = now + Duration::minutes(PASSWORD_RESET_TTL_MINUTES);
+
+    let new_password_reset = NewStaffPasswordReset {
+        staff_id: staff_data.id,
+        token_hash: &token_hash,
+        expires_at: &expires_at,
+        created_at: &now,
+    };
+
+    // Only the newest token works, earlier ones are retired along the way
+    conn.transaction::<_, AppError, _>(|conn| {
+        invalidate_password_resets(conn, staff_data.id, now)?;
+
+        diesel::insert_into(staff_password_resets::table)
+            .values(&new_password_reset)
+            .execute(conn)?;
+
+        Ok(())
+    })?;
+
+    Ok(Some(token))
+}
+
+fn invalid_reset_token() -> AppError {
+    AppError::BadRequest("Invalid or expired reset token.".to_string())
+}
+
+pub fn verify_password_reset(conn: &mut PgConnection, token: &str) -> Result<(), AppError> {
+    staff_password_resets::table
+        .inner_join(staff)
+        .filter(staff_password_resets::token_hash.eq(hash_token(token)))
+        .filter(staff_password_resets::used_at.is_null())
+        .filter(staff_password_resets::expires_at.gt(Utc::now()))
+        .filter(is_active.eq(true))
+        .select(staff_password_resets::id)
+        .first::<i32>(conn)
+        .optional()?
+        .ok_or_else(invalid_reset_token)?;
+
+    Ok(())
+}
+
+pub fn confirm_password_reset(
+    conn: &mut PgConnection,
+    token: &str,
+    new_password: &str,
+) -> Result<(), AppError> {
+    let hashed_password = hash(new_password, DEFAULT_COST).expect("Failed to hash password");
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        let now = Utc::now();
+
+        // Claiming the token in the same statement that checks it means two
+        // concurrent confirmations cannot both use it
+        let staff_id = diesel::update(
+            staff_password_resets::table
+                .filter(staff_password_resets::token_hash.eq(hash_token(token)))
+                .filter(staff_password_resets::used_at.is_null())
+                .filter(staff_password_resets::expires_at.gt(now)),
+        )
+        .set(staff_password_resets::used_at.eq(now))
+        .returning(staff_password_resets::staff_id)
+        .get_result::<i32>(conn)
+        .optional()?
+        .ok_or_else(invalid_reset_token)?;
+
+        let staff_data = staff
+            .filter(id.eq(staff_id))
+            .filter(is_active.eq(true))
+            .first::<Staff>(conn)
+            .optional()?
+            .ok_or_else(invalid_reset_token)?;
+
+        let updated_staff = diesel::update(staff.filter(id.eq(staff_id)))
+            .set((
+                password.eq(&hashed_password),
+                must_change_password.eq(false),
+                updated_at.eq(now),
+            ))
+            .get_result::<Staff>(conn)?;
+
+        // Nobody is signed in here, the reset token stands in for the staff member
+        record_audit_event(
+            conn,
+            Some(staff_id),
+            AuditEntity::Staff,
+            staff_id,
+            "reset_password",
+            Some(&staff_data),
+            Some(&updated_staff),
+        )?;
+
+        invalidate_password_resets(conn, staff_id, now)?;
+        revoke_staff_sessions(conn, staff_id)?;
+
+        Ok(())
+    })
+}
+
+// Marks every outstanding reset token of a staff member as used
+fn invalidate_password_resets(
+    conn: &mut PgConnection,
+    staff_id: i32,
+    now: DateTime<Utc>,
+) -> Result<(), AppError> {
+    diesel::update(
+        staff_password_resets::table
+            .filter(staff_password_resets::staff_id.eq(staff_id))
+            .filter(staff_password_resets::used_at.is_null()),
+    )
+    .set(staff_password_resets::used_at.eq(now))
+    .execute(conn)?;
+
+    Ok(())
+}
 
-        Ok((staff_data.id, staff_data.name, jwt))
-    } else {
-        Err("Invalid email or password.".to_string())
+// Sets a temporary password from the admin CLI, also lifting any login
+// lockout and signing the staff member out everywhere.
+pub fn reset_staff_password_by_email(
+    conn: &mut PgConnection,
+    email_input: &str,
+    new_password: &str,
+) -> Result<(), AppError> {
+    let hashed_password = hash(new_password, DEFAULT_COST).expect("Failed to hash password");
+
+    conn.transaction::<_, AppError, _>(|conn| {
+        let staff_data = staff
+            .filter(email.eq(email_input))
+            .for_update()
+            .first::<Staff>(conn)
+            .or_not_found("Staff not found.")?;
+
+        let updated_staff = diesel::update(staff.filter(id.eq(staff_data.id)))
+            .set((
+                password.eq(&hashed_password),
+                must_change_password.eq(true),
+                failed_login_attempts.eq(0),
+                locked_until.eq(None::<DateTime<Utc>>),
+                updated_at.eq(Utc::now()),
+            ))
+            .get_result::<Staff>(conn)?;
+
+        record_audit_event(
+            conn,
+            None,
+            AuditEntity::Staff,
+            staff_data.id,
+            "reset_password",
+            Some(&staff_data),
+            Some(&updated_staff),
+        )?;
+
+        revoke_staff_sessions(conn, staff_data.id)?;
+
+        Ok(())
+    })
+}
+
+// Swaps a refresh token for a new access token and a new refresh token, the
+// old refresh token stops working.
+pub fn refresh_session(
+    conn: &mut PgConnection,
+    refresh_token: &str,
+    jwt_config: &JwtConfig,
+) -> Result<AuthTokens, AppError> {
+    let now = Utc::now();
+    let invalid_token = || AppError::Unauthorized("Invalid or expired refresh token.".to_string());
+    let refresh_token_hash = hash_token(refresh_token);
+
+    let (session_id, staff_data) = staff_sessions::table
+        .inner_join(staff)
+        .filter(staff_sessions::refresh_token_hash.eq(&refresh_token_hash))
+        .filter(staff_sessions::revoked_at.is_null())
+        .filter(staff_sessions::expires_at.gt(now))
+        .filter(is_active.eq(true))
+        .select((staff_sessions::id, staff::all_columns()))
+        .first::<(i32, Staff)>(conn)
+        .optional()?
+        .ok_or_else(invalid_token)?;
+
+    let new_refresh_token = generate_token();
+    let expires_at = now + Duration::days(jwt_config.refresh_token_ttl_days);
+
+    // Only rotates if the token is still the current one, so of two requests
+    // racing with the same refresh token only the first gets new tokens
+    let rotated = diesel::update(
+        staff_sessions::table
+            .find(session_id)
+            .filter(staff_sessions::refresh_token_hash.eq(&refresh_token_hash))
+            .filter(staff_sessions::revoked_at.is_null()),
+    )
+    .set((
+        staff_sessions::refresh_token_hash.eq(hash_token(&new_refresh_token)),
+        staff_sessions::expires_at.eq(expires_at),
+        staff_sessions::updated_at.eq(now),
+    ))
+    .returning(staff_sessions::id)
+    .get_result::<i32>(conn)
+    .optional()?;
+
+    // The token was used twice, treat it as stolen and end the session
+    if rotated.is_none() {
+        revoke_session(conn, session_id)?;
+        return Err(invalid_token());
     }
+
+    let token = generate_jwt(
+        staff_data.id,
+        Role::from_position(&staff_data.position),
+        session_id,
+        jwt_config,
+    )?;
+
+    Ok(AuthTokens {
+        token,
+        refresh_token: new_refresh_token,
+    })
+}
+
+pub fn revoke_session(conn: &mut PgConnection, session_id: i32) -> Result<(), AppError> {
+    diesel::update(
+        staff_sessions::table
+            .find(session_id)
+            .filter(staff_sessions::revoked_at.is_null()),
+    )
+    .set(staff_sessions::revoked_at.eq(Utc::now()))
+    .execute(conn)?;
+
+    Ok(())
+}
+
+fn revoke_staff_sessions(conn: &mut PgConnection, staff_id: i32) -> Result<(), AppError> {
+    diesel::update(
+        staff_sessions::table
+            .filter(staff_sessions::staff_id.eq(staff_id))
+            .filter(staff_sessions::revoked_at.is_null()),
+    )
+    .set(staff_sessions::revoked_at.eq(Utc::now()))
+    .execute(conn)?;
+
+    Ok(())
+}
+
+// Access tokens are only honoured while their session is live and the staff
+// member is still active. Returns whether the staff member still has to
+// change their password, or None when the token must be rejected.
+pub fn find_active_session(
+    conn: &mut PgConnection,
+    session_id: i32,
+    staff_id: i32,
+) -> Result<Option<bool>, AppError> {
+    staff_sessions::table
+        .inner_join(staff)
+        .filter(staff_sessions::id.eq(session_id))
+        .filter(staff_sessions::staff_id.eq(staff_id))
+        .filter(staff_sessions::revoked_at.is_null())
+        .filter(staff_sessions::expires_at.gt(Utc::now()))
+        .filter(is_active.eq(true))
+        .select(must_change_password)
+        .first::<bool>(conn)
+        .optional()
+        .map_err(AppError::from)
+}
+
+fn create_session(
+    conn: &mut PgConnection,
+    staff_data: &Staff,
+    jwt_config: &JwtConfig,
+) -> Result<AuthTokens, AppError> {
+    let now = Utc::now();
+    let refresh_token = generate_token();
+    let refresh_token_hash = hash_token(&refresh_token);
+    let expires_at = now + Duration::days(jwt_config.refresh_token_ttl_days);
+
+    let new_session = NewStaffSession {
+        staff_id: staff_data.id,
+        refresh_token_hash: &refresh_token_hash,
+        expires_at: &expires_at,
+        created_at: &now,
+        updated_at: &now,
+    };
+
+    let session_id = diesel::insert_into(staff_sessions::table)
+        .values(&new_session)
+        .returning(staff_sessions::id)
+        .get_result::<i32>(conn)?;
+
+    let token = generate_jwt(
+        staff_data.id,
+        Role::from_position(&staff_data.position),
+        session_id,
+        jwt_config,
+    )?;
+
+    Ok(AuthTokens {
+        token,
+        refresh_token,
+    })
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
 }
 
-fn generate_jwt(staff_id: i32) -> Result<String, String> {
+// Only the SHA-256 of refresh and password reset tokens is stored, so a
+// database leak does not hand out working tokens.
+fn hash_token(refresh_token: &str) -> String {
+    hex::encode(Sha256::digest(refresh_token.as_bytes()))
+}
+
+fn generate_jwt(
+    staff_id: i32,
+    role: Role,
+    session_id: i32,
+    jwt_config: &JwtConfig,
+) -> Result<String, AppError> {
     let expiration = Utc::now()
-        .checked_add_signed(Duration::days(1))
+        .checked_add_signed(Duration::minutes(jwt_config.access_token_ttl_minutes))
         .expect("Invalid timestamp")
         .timestamp();
 
     let claims = Claims {
         sub: staff_id.to_owned(),
+        role,
+        sid: session_id,
         exp: expiration as usize,
     };
 
-    let secret_key = staff_jwt_secret();
     encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(secret_key.as_ref()),
+        &EncodingKey::from_secret(jwt_config.secret.as_ref()),
     )
-    .map_err(|_| "Token generation failed".to_string())
+    .map_err(|_| AppError::Internal("Token generation failed.".to_string()))
 }
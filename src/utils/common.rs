@@ -1,16 +1,164 @@
+use crate::middlewares::request_id::current_request_id;
+use crate::utils::response::StandardResponse;
+use crate::utils::validation::ValidationErrors;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use diesel::result::DatabaseErrorKind;
 use serde::Deserialize;
-use thiserror::Error; 
+use thiserror::Error;
 
 #[derive(Deserialize)]
 pub struct PaginationParams {
     pub page: Option<i64>,
-    pub page_size: Option<i64>, 
+    pub page_size: Option<i64>,
 }
 
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Database error: {0}")]
     DatabaseError(#[from] diesel::result::Error), // Automatically converts diesel errors
-    #[error("Bad request")]
+    #[error("{0}")]
     BadRequest(String), // 400
-}
\ No newline at end of file
+    #[error("{0}")]
+    Unauthorized(String), // 401
+    #[error("{0}")]
+    Forbidden(String), // 403
+    #[error("{0}")]
+    NotFound(String), // 404
+    #[error("{0}")]
+    Conflict(String), // 409
+    #[error("Validation failed.")]
+    Validation(ValidationErrors), // 422
+    #[error("{0}")]
+    TooManyRequests(String), // 429
+    #[error("{0}")]
+    Internal(String), // 500
+    #[error("{0}")]
+    ServiceUnavailable(String), // 503
+}
+
+impl From<r2d2::Error> for AppError {
+    fn from(_: r2d2::Error) -> Self {
+        AppError::Internal("Failed to get DB connection.".to_string())
+    }
+}
+
+impl From<actix_web::error::BlockingError> for AppError {
+    fn from(_: actix_web::error::BlockingError) -> Self {
+        AppError::Internal("Blocking task failed.".to_string())
+    }
+}
+
+impl AppError {
+    // Stable identifier clients can match on, messages may change over time
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::DatabaseError(diesel::result::Error::NotFound) => "NOT_FOUND",
+            AppError::DatabaseError(diesel::result::Error::DatabaseError(
+                DatabaseErrorKind::UniqueViolation,
+                _,
+            )) => "CONFLICT",
+            AppError::DatabaseError(diesel::result::Error::DatabaseError(
+                DatabaseErrorKind::ForeignKeyViolation,
+                _,
+            )) => "BAD_REQUEST",
+            AppError::DatabaseError(_) => "INTERNAL_ERROR",
+            AppError::BadRequest(_) => "BAD_REQUEST",
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::Conflict(_) => "CONFLICT",
+            AppError::Validation(_) => "VALIDATION_ERROR",
+            AppError::TooManyRequests(_) => "TOO_MANY_REQUESTS",
+            AppError::Internal(_) => "INTERNAL_ERROR",
+            AppError::ServiceUnavailable(_) => "SERVICE_UNAVAILABLE",
+        }
+    }
+
+    // Database errors are not shown to clients as they are
+    fn message(&self) -> String {
+        match self {
+            AppError::DatabaseError(diesel::result::Error::NotFound) => {
+                "Resource not found.".to_string()
+            }
+            AppError::DatabaseError(diesel::result::Error::DatabaseError(
+                DatabaseErrorKind::UniqueViolation,
+                _,
+            )) => "Resource already exists.".to_string(),
+            AppError::DatabaseError(diesel::result::Error::DatabaseError(
+                DatabaseErrorKind::ForeignKeyViolation,
+                _,
+            )) => "Referenced resource does not exist.".to_string(),
+            AppError::DatabaseError(_) => "Internal server error.".to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_)
+            | AppError::DatabaseError(diesel::result::Error::DatabaseError(
+                DatabaseErrorKind::ForeignKeyViolation,
+                _,
+            )) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) | AppError::DatabaseError(diesel::result::Error::NotFound) => {
+                StatusCode::NOT_FOUND
+            }
+            AppError::Conflict(_)
+            | AppError::DatabaseError(diesel::result::Error::DatabaseError(
+                DatabaseErrorKind::UniqueViolation,
+                _,
+            )) => StatusCode::CONFLICT,
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::DatabaseError(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if self.status_code().is_server_error() {
+            tracing::error!(error = ?self, "request failed");
+        }
+
+        let response = match self {
+            AppError::Validation(errors) => StandardResponse::<()>::error_with_fields(
+                self.code(),
+                &self.message(),
+                errors.clone(),
+            ),
+            _ => StandardResponse::<()>::error(self.code(), &self.message()),
+        };
+
+        HttpResponse::build(self.status_code()).json(response.with_request_id(current_request_id()))
+    }
+}
+
+pub trait DieselResultExt<T> {
+    fn or_not_found(self, message: &str) -> Result<T, AppError>;
+    fn or_conflict(self, message: &str) -> Result<T, AppError>;
+}
+
+// Lets services name what was missing or duplicated instead of leaking the
+// raw diesel error
+impl<T> DieselResultExt<T> for Result<T, diesel::result::Error> {
+    fn or_not_found(self, message: &str) -> Result<T, AppError> {
+        self.map_err(|err| match err {
+            diesel::result::Error::NotFound => AppError::NotFound(message.to_string()),
+            err => AppError::DatabaseError(err),
+        })
+    }
+
+    fn or_conflict(self, message: &str) -> Result<T, AppError> {
+        self.map_err(|err| match err {
+            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+                AppError::Conflict(message.to_string())
+            }
+            err => AppError::DatabaseError(err),
+        })
+    }
+}
@@ -0,0 +1,22 @@
+use crate::utils::common::AppError;
+
+// Hands password reset tokens to the staff member they were issued for.
+// Tokens must never end up in logs, whatever the implementation.
+pub trait Mailer: Send + Sync {
+    fn send_password_reset(&self, email: &str, token: &str) -> Result<(), AppError>;
+}
+
+// Used until a real mail transport is configured. The token is dropped, an
+// operator can issue one with `my-rooms-admin issue-reset-token` instead.
+pub struct NoopMailer;
+
+impl Mailer for NoopMailer {
+    fn send_password_reset(&self, email: &str, _token: &str) -> Result<(), AppError> {
+        tracing::warn!(
+            %email,
+            "password reset requested but no mailer is configured, token discarded"
+        );
+
+        Ok(())
+    }
+}
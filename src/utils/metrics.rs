@@ -0,0 +1,156 @@
+use crate::config::database::DbPool;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use r2d2::event::{CheckoutEvent, TimeoutEvent};
+use r2d2::HandleEvent;
+
+// Everything exported on /metrics. Handles are cheap to clone and share one
+// underlying value, so the pool event handler can hold its own copies.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests_total: IntCounterVec,
+    pub http_request_duration_seconds: HistogramVec,
+    pub db_pool_connections: IntGaugeVec,
+    pub db_pool_wait_seconds: Histogram,
+    pub db_pool_timeouts_total: IntCounter,
+    pub login_attempts_total: IntCounterVec,
+    pub reservations_created_total: IntCounter,
+    pub rooms_total: IntGauge,
+    pub rooms_occupied: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests_total = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time taken to answer HTTP requests",
+            ),
+            &["method", "route"],
+        )
+        .unwrap();
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "Database pool connections by state"),
+            &["state"],
+        )
+        .unwrap();
+        let db_pool_wait_seconds = Histogram::with_opts(HistogramOpts::new(
+            "db_pool_wait_seconds",
+            "Time spent waiting for a database connection",
+        ))
+        .unwrap();
+        let db_pool_timeouts_total = IntCounter::new(
+            "db_pool_timeouts_total",
+            "Database connection checkouts that timed out",
+        )
+        .unwrap();
+        let login_attempts_total = IntCounterVec::new(
+            Opts::new("login_attempts_total", "Staff login attempts by result"),
+            &["result"],
+        )
+        .unwrap();
+        let reservations_created_total =
+            IntCounter::new("reservations_created_total", "Reservations created").unwrap();
+        let rooms_total = IntGauge::new("rooms_total", "Rooms in the hotel").unwrap();
+        let rooms_occupied =
+            IntGauge::new("rooms_occupied", "Rooms with a checked in reservation").unwrap();
+
+        registry
+            .register(Box::new(http_requests_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(http_request_duration_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(db_pool_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(db_pool_wait_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(db_pool_timeouts_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(login_attempts_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(reservations_created_total.clone()))
+            .unwrap();
+        registry.register(Box::new(rooms_total.clone())).unwrap();
+        registry.register(Box::new(rooms_occupied.clone())).unwrap();
+
+        Metrics {
+            registry,
+            http_requests_total,
+            http_request_duration_seconds,
+            db_pool_connections,
+            db_pool_wait_seconds,
+            db_pool_timeouts_total,
+            login_attempts_total,
+            reservations_created_total,
+            rooms_total,
+            rooms_occupied,
+        }
+    }
+
+    pub fn pool_event_handler(&self) -> PoolEventHandler {
+        PoolEventHandler {
+            wait_seconds: self.db_pool_wait_seconds.clone(),
+            timeouts_total: self.db_pool_timeouts_total.clone(),
+        }
+    }
+
+    pub fn record_pool_state(&self, pool: &DbPool) {
+        let state = pool.state();
+
+        self.db_pool_connections
+            .with_label_values(&["idle"])
+            .set(state.idle_connections.into());
+        self.db_pool_connections
+            .with_label_values(&["in_use"])
+            .set((state.connections - state.idle_connections).into());
+    }
+
+    // Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .unwrap_or_default();
+
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+pub struct PoolEventHandler {
+    wait_seconds: Histogram,
+    timeouts_total: IntCounter,
+}
+
+impl HandleEvent for PoolEventHandler {
+    fn handle_checkout(&self, event: CheckoutEvent) {
+        self.wait_seconds.observe(event.duration().as_secs_f64());
+    }
+
+    fn handle_timeout(&self, event: TimeoutEvent) {
+        self.wait_seconds.observe(event.timeout().as_secs_f64());
+        self.timeouts_total.inc();
+    }
+}
@@ -1,2 +1,5 @@
 pub mod common;
-pub mod response;
\ No newline at end of file
+pub mod response;
+pub mod validation;
+pub mod metrics;
+pub mod mailer;
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::utils::validation::ValidationErrors;
 use serde::Serialize;
 
 #[derive(Serialize)]
@@ -26,6 +27,16 @@ pub enum StandardResponse<T> {
         data: T,
         pagination: PaginationMeta,
     },
+    Error {
+        status: bool,
+        code: String,
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        errors: Option<ValidationErrors>,
+        // Quoted by clients in support tickets to find the request in the logs
+        #[serde(skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>,
+    },
 }
 
 impl<T> StandardResponse<T> {
@@ -35,7 +46,7 @@ impl<T> StandardResponse<T> {
             message: message.to_string(),
         }
     }
-    
+
     pub fn success_with_data(data: T, message: &str) -> Self {
         StandardResponse::WithData {
             status: true,
@@ -45,7 +56,7 @@ impl<T> StandardResponse<T> {
     }
 
     pub fn success_with_pagination(data: T, message: &str, pagination: PaginationMeta) -> Self {
-        StandardResponse::WithDataAndPagination  {
+        StandardResponse::WithDataAndPagination {
             status: true,
             message: message.to_string(),
             data,
@@ -53,10 +64,30 @@ impl<T> StandardResponse<T> {
         }
     }
 
-    pub fn error(message: &str) -> Self {
-        StandardResponse::MessageOnly {
+    pub fn error(code: &str, message: &str) -> Self {
+        StandardResponse::Error {
             status: false,
+            code: code.to_string(),
             message: message.to_string(),
+            errors: None,
+            request_id: None,
+        }
+    }
+
+    pub fn error_with_fields(code: &str, message: &str, errors: ValidationErrors) -> Self {
+        StandardResponse::Error {
+            status: false,
+            code: code.to_string(),
+            message: message.to_string(),
+            errors: Some(errors),
+            request_id: None,
+        }
+    }
+
+    pub fn with_request_id(mut self, id: Option<String>) -> Self {
+        if let StandardResponse::Error { request_id, .. } = &mut self {
+            *request_id = id;
         }
+        self
     }
 }
@@ -0,0 +1,210 @@
+use crate::services::customer_service::normalize_phone_number;
+use crate::utils::common::AppError;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+pub const MAX_PHONE_NUMBER_LENGTH: usize = 15;
+pub const DEFAULT_PAGE_SIZE: i64 = 10;
+
+// Problems found in a request, keyed by the name of the offending field
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct ValidationErrors(BTreeMap<String, Vec<String>>);
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn single(field: &str, message: &str) -> Self {
+        let mut errors = Self::new();
+        errors.add(field, message);
+        errors
+    }
+
+    pub fn add(&mut self, field: &str, message: &str) {
+        self.0
+            .entry(field.to_string())
+            .or_default()
+            .push(message.to_string());
+    }
+
+    pub fn into_result(self) -> Result<(), AppError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Validation(self))
+        }
+    }
+
+    pub fn required(&mut self, field: &str, value: &str, max_length: usize) {
+        if value.trim().is_empty() {
+            self.add(field, "must not be empty");
+        } else if value.chars().count() > max_length {
+            self.add(field, &format!("must be at most {} characters", max_length));
+        }
+    }
+
+    pub fn email(&mut self, field: &str, value: &str, max_length: usize) {
+        self.required(field, value, max_length);
+
+        if !value.trim().is_empty() && !is_valid_email(value.trim()) {
+            self.add(field, "must be a valid email address");
+        }
+    }
+
+    // Checked after normalization as that is what gets stored
+    pub fn phone_number(&mut self, field: &str, value: &str) {
+        let phone_number = normalize_phone_number(value);
+        let digits = phone_number.chars().filter(char::is_ascii_digit).count();
+
+        if digits == 0 {
+            self.add(field, "must contain digits");
+        } else if phone_number.len() > MAX_PHONE_NUMBER_LENGTH {
+            self.add(
+                field,
+                &format!("must be at most {} characters", MAX_PHONE_NUMBER_LENGTH),
+            );
+        }
+    }
+
+    pub fn password(&mut self, field: &str, value: &str) {
+        if value.chars().count() < MIN_PASSWORD_LENGTH {
+            self.add(
+                field,
+                &format!("must be at least {} characters", MIN_PASSWORD_LENGTH),
+            );
+        }
+    }
+
+    pub fn positive(&mut self, field: &str, value: i32) {
+        if value <= 0 {
+            self.add(field, "must be greater than 0");
+        }
+    }
+}
+
+pub trait Validate {
+    fn validate(&self) -> Result<(), AppError>;
+}
+
+// Page and page size of a listing with their defaults applied. Anything
+// below 1 would end up as a negative OFFSET or an empty LIMIT.
+pub fn pagination(page: Option<i64>, page_size: Option<i64>) -> Result<(i64, i64), AppError> {
+    let page = page.unwrap_or(1);
+    let page_size = page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+
+    let mut errors = ValidationErrors::new();
+    if page < 1 {
+        errors.add("page", "must be at least 1");
+    }
+    if page_size < 1 {
+        errors.add("page_size", "must be at least 1");
+    }
+    errors.into_result()?;
+
+    Ok((page, page_size))
+}
+
+fn is_valid_email(value: &str) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+
+    !local.is_empty()
+        && !domain.contains('@')
+        && !value.chars().any(char::is_whitespace)
+        && domain.contains('.')
+        && domain.split('.').all(|part| !part.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(errors: &ValidationErrors) -> Vec<&str> {
+        errors.0.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn required_rejects_blank_and_overlong_values() {
+        let mut errors = ValidationErrors::new();
+        errors.required("blank", "   ", 10);
+        errors.required("long", "abcdefghijk", 10);
+        errors.required("ok", "abcdefghij", 10);
+
+        assert_eq!(fields(&errors), ["blank", "long"]);
+    }
+
+    #[test]
+    fn email_accepts_plain_addresses() {
+        for email in ["guest@example.com", " guest@mail.example.co ", "a.b+c@d.io"] {
+            let mut errors = ValidationErrors::new();
+            errors.email("email", email, 100);
+            assert!(errors.0.is_empty(), "{} should be valid", email);
+        }
+    }
+
+    #[test]
+    fn email_rejects_malformed_addresses() {
+        for email in [
+            "guest",
+            "@example.com",
+            "guest@",
+            "guest@example",
+            "guest@@example.com",
+            "guest@example..com",
+            "gu est@example.com",
+        ] {
+            let mut errors = ValidationErrors::new();
+            errors.email("email", email, 100);
+            assert_eq!(fields(&errors), ["email"], "{} should be invalid", email);
+        }
+    }
+
+    #[test]
+    fn phone_number_needs_digits_and_a_bounded_length() {
+        let mut errors = ValidationErrors::new();
+        errors.phone_number("no_digits", "+() -");
+        errors.phone_number("too_long", "+1 234 567 890 123 45");
+        // Formatting is stripped before the length is checked
+        errors.phone_number("formatted", "+1 (234) 567-8901");
+
+        assert_eq!(fields(&errors), ["no_digits", "too_long"]);
+    }
+
+    #[test]
+    fn password_needs_the_minimum_length() {
+        let mut errors = ValidationErrors::new();
+        errors.password("short", "1234567");
+        errors.password("ok", "12345678");
+
+        assert_eq!(fields(&errors), ["short"]);
+    }
+
+    #[test]
+    fn positive_rejects_zero_and_negatives() {
+        let mut errors = ValidationErrors::new();
+        errors.positive("zero", 0);
+        errors.positive("negative", -1);
+        errors.positive("ok", 1);
+
+        assert_eq!(fields(&errors), ["negative", "zero"]);
+    }
+
+    #[test]
+    fn pagination_applies_defaults() {
+        assert_eq!(pagination(None, None).unwrap(), (1, DEFAULT_PAGE_SIZE));
+        assert_eq!(pagination(Some(3), Some(25)).unwrap(), (3, 25));
+    }
+
+    #[test]
+    fn pagination_rejects_values_below_one() {
+        match pagination(Some(0), Some(-5)) {
+            Err(AppError::Validation(errors)) => {
+                assert_eq!(fields(&errors), ["page", "page_size"])
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Helpers shared by the database backed integration tests. They run against
+// TEST_DATABASE_URL inside a transaction that is never committed, so the
+// database is left as it was found. The tests are ignored by default, run them
+// with `cargo test -- --ignored` once the database is set up.
+#![allow(dead_code)]
+
+use chrono::{NaiveDate, Utc};
+use diesel::prelude::*;
+use dotenv::dotenv;
+use my_rooms_be::config::database::run_pending_migrations;
+use my_rooms_be::models::reservation::{CreateOrUpdateReservationRequest, ReservationStatus};
+use my_rooms_be::models::room::{NewRoom, NewRoomTypes};
+use my_rooms_be::models::staff::{NewStaff, Role};
+use my_rooms_be::schema::{room_types, rooms, staff};
+use std::env;
+use std::sync::Once;
+
+static MIGRATIONS: Once = Once::new();
+
+// Panics when TEST_DATABASE_URL is not set, an ignored test that was asked
+// for must not pass without checking anything
+pub fn test_connection() -> PgConnection {
+    dotenv().ok();
+
+    let url = env::var("TEST_DATABASE_URL")
+        .expect("TEST_DATABASE_URL must be set to run the database tests");
+
+    let mut conn = PgConnection::establish(&url).expect("Failed to connect to test database");
+
+    MIGRATIONS.call_once(|| {
+        run_pending_migrations(&mut conn).expect("Failed to migrate test database");
+    });
+
+    conn.begin_test_transaction()
+        .expect("Failed to start test transaction");
+
+    conn
+}
+
+// Names and emails have to be unique across whatever the database holds
+pub fn unique(prefix: &str) -> String {
+    format!("{}-{}", prefix, rand::random::<u32>())
+}
+
+pub fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+pub fn insert_staff(conn: &mut PgConnection) -> i32 {
+    insert_staff_with_password(conn, "not-a-real-hash").0
+}
+
+// Takes the stored hash as is, returns the id and the generated email
+pub fn insert_staff_with_password(conn: &mut PgConnection, password: &str) -> (i32, String) {
+    let now = Utc::now();
+    let email = format!("{}@example.com", unique("staff"));
+
+    diesel::insert_into(staff::table)
+        .values(&NewStaff {
+            name: "Test Staff",
+            email: &email,
+            password,
+            position: Role::Admin.as_str(),
+            must_change_password: false,
+            created_at: &now,
+            updated_at: &now,
+        })
+        .returning(staff::id)
+        .get_result(conn)
+        .map(|staff_id| (staff_id, email))
+        .unwrap()
+}
+
+pub fn insert_room_type(conn: &mut PgConnection, staff_id: i32) -> i32 {
+    let now = Utc::now();
+
+    diesel::insert_into(room_types::table)
+        .values(&NewRoomTypes {
+            type_name: &unique("Type"),
+            description: None,
+            price_per_night: 100,
+            created_at: &now,
+            updated_at: &now,
+            created_by: Some(staff_id),
+            updated_by: Some(staff_id),
+        })
+        .returning(room_types::id)
+        .get_result(conn)
+        .unwrap()
+}
+
+pub fn insert_room(conn: &mut PgConnection, type_id: i32, staff_id: i32) -> i32 {
+    let now = Utc::now();
+
+    diesel::insert_into(rooms::table)
+        .values(&NewRoom {
+            room_name: &unique("Room"),
+            type_id: Some(type_id),
+            capacity: &2,
+            is_available: &true,
+            created_by: staff_id,
+            updated_by: staff_id,
+            created_at: &now,
+            updated_at: &now,
+        })
+        .returning(rooms::id)
+        .get_result(conn)
+        .unwrap()
+}
+
+// A confirmed stay for a first time guest with a fresh email
+pub fn reservation_request(
+    room_id: i32,
+    check_in_date: &str,
+    check_out_date: &str,
+) -> CreateOrUpdateReservationRequest {
+    CreateOrUpdateReservationRequest {
+        room_id,
+        check_in_date: date(check_in_date),
+        check_out_date: date(check_out_date),
+        status: ReservationStatus::Confirmed,
+        customer_contact_id: None,
+        full_name: Some("Test Guest".to_string()),
+        email: Some(format!("{}@example.com", unique("guest"))),
+        phone_number: Some(format!("+1{}", rand::random::<u32>())),
+    }
+}
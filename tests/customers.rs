@@ -0,0 +1,129 @@
+mod common;
+
+use common::{insert_room, insert_room_type, insert_staff, reservation_request, test_connection};
+use diesel::prelude::*;
+use my_rooms_be::models::customer_contact::{CustomerContact, MergeCustomersRequest};
+use my_rooms_be::schema::{customer_contacts, reservations};
+use my_rooms_be::services::customer_service::merge_customers;
+use my_rooms_be::services::reservation_service::{create_reservation, update_reservation_by_id};
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn reservation_update_corrects_the_matched_contact() {
+    let mut conn = test_connection();
+    let staff_id = insert_staff(&mut conn);
+    let type_id = insert_room_type(&mut conn, staff_id);
+    let room_id = insert_room(&mut conn, type_id, staff_id);
+
+    let request = reservation_request(room_id, "2030-03-01", "2030-03-03");
+    create_reservation(&mut conn, &request, staff_id).unwrap();
+
+    let (reservation_id, contact_id) = reservations::table
+        .filter(reservations::room_id.eq(room_id))
+        .select((reservations::id, reservations::customer_contact_id))
+        .first::<(i32, i32)>(&mut conn)
+        .unwrap();
+
+    let mut corrected = request;
+    corrected.full_name = Some("Corrected Guest".to_string());
+    corrected.phone_number = Some("+15550100".to_string());
+    update_reservation_by_id(&mut conn, reservation_id, &corrected, staff_id).unwrap();
+
+    let contact = customer_contacts::table
+        .find(contact_id)
+        .first::<CustomerContact>(&mut conn)
+        .unwrap();
+    assert_eq!(contact.full_name, "Corrected Guest");
+    assert_eq!(contact.phone_number, "+15550100");
+
+    let reservation_contact_id = reservations::table
+        .find(reservation_id)
+        .select(reservations::customer_contact_id)
+        .first::<i32>(&mut conn)
+        .unwrap();
+    assert_eq!(reservation_contact_id, contact_id);
+}
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn merge_accepts_repeated_duplicate_ids() {
+    let mut conn = test_connection();
+    let staff_id = insert_staff(&mut conn);
+    let type_id = insert_room_type(&mut conn, staff_id);
+    let room_id = insert_room(&mut conn, type_id, staff_id);
+
+    create_reservation(
+        &mut conn,
+        &reservation_request(room_id, "2030-03-01", "2030-03-03"),
+        staff_id,
+    )
+    .unwrap();
+    create_reservation(
+        &mut conn,
+        &reservation_request(room_id, "2030-03-05", "2030-03-07"),
+        staff_id,
+    )
+    .unwrap();
+
+    let contact_ids = reservations::table
+        .filter(reservations::room_id.eq(room_id))
+        .order(reservations::check_in_date)
+        .select(reservations::customer_contact_id)
+        .load::<i32>(&mut conn)
+        .unwrap();
+    let (kept_id, duplicate_id) = (contact_ids[0], contact_ids[1]);
+
+    let request = MergeCustomersRequest {
+        duplicate_ids: vec![duplicate_id, duplicate_id],
+    };
+    merge_customers(&mut conn, kept_id, &request).unwrap();
+
+    let remaining = customer_contacts::table
+        .find(duplicate_id)
+        .count()
+        .get_result::<i64>(&mut conn)
+        .unwrap();
+    assert_eq!(remaining, 0);
+
+    let moved = reservations::table
+        .filter(reservations::room_id.eq(room_id))
+        .filter(reservations::customer_contact_id.eq(kept_id))
+        .count()
+        .get_result::<i64>(&mut conn)
+        .unwrap();
+    assert_eq!(moved, 2);
+}
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn shared_phone_number_does_not_take_over_another_guest() {
+    let mut conn = test_connection();
+    let staff_id = insert_staff(&mut conn);
+    let type_id = insert_room_type(&mut conn, staff_id);
+    let room_id = insert_room(&mut conn, type_id, staff_id);
+
+    let mut alice = reservation_request(room_id, "2030-04-01", "2030-04-03");
+    alice.full_name = Some("Alice".to_string());
+    create_reservation(&mut conn, &alice, staff_id).unwrap();
+
+    // Same phone, different email, e.g. a shared office line
+    let mut bob = reservation_request(room_id, "2030-04-05", "2030-04-07");
+    bob.full_name = Some("Bob".to_string());
+    bob.phone_number = alice.phone_number.clone();
+    create_reservation(&mut conn, &bob, staff_id).unwrap();
+
+    let contacts = reservations::table
+        .inner_join(customer_contacts::table)
+        .filter(reservations::room_id.eq(room_id))
+        .order(reservations::check_in_date)
+        .select(customer_contacts::all_columns)
+        .load::<CustomerContact>(&mut conn)
+        .unwrap();
+
+    assert_eq!(contacts.len(), 2);
+    assert_ne!(contacts[0].id, contacts[1].id);
+    assert_eq!(contacts[0].full_name, "Alice");
+    assert_eq!(&contacts[0].email, alice.email.as_ref().unwrap());
+    assert_eq!(contacts[1].full_name, "Bob");
+    assert_eq!(&contacts[1].email, bob.email.as_ref().unwrap());
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::{insert_staff_with_password, test_connection};
+use diesel::prelude::*;
+use my_rooms_be::config::app::JwtConfig;
+use my_rooms_be::config::auth::MAX_FAILED_LOGIN_ATTEMPTS;
+use my_rooms_be::models::staff::Staff;
+use my_rooms_be::models::staff_session::AuthTokens;
+use my_rooms_be::schema::staff;
+use my_rooms_be::services::staff_service::{finish_login, start_login};
+use my_rooms_be::utils::common::AppError;
+
+const PASSWORD: &str = "Correct-Horse-1";
+
+fn login(
+    conn: &mut PgConnection,
+    email: &str,
+    password: &str,
+) -> Result<(Staff, AuthTokens), AppError> {
+    let jwt_config = JwtConfig {
+        secret: "test-secret".to_string(),
+        ..JwtConfig::default()
+    };
+
+    let attempt = start_login(conn, email, None)?.check_password(password);
+    finish_login(conn, attempt, None, &jwt_config)
+}
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn locked_account_accepts_no_password() {
+    let mut conn = test_connection();
+    let hashed_password = bcrypt::hash(PASSWORD, 4).unwrap();
+    let (staff_id, email) = insert_staff_with_password(&mut conn, &hashed_password);
+
+    login(&mut conn, &email, PASSWORD).unwrap();
+
+    for _ in 0..MAX_FAILED_LOGIN_ATTEMPTS {
+        let result = login(&mut conn, &email, "wrong-password");
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+    let locked_until = staff::table
+        .find(staff_id)
+        .select(staff::locked_until)
+        .first::<Option<DateTime<Utc>>>(&mut conn)
+        .unwrap();
+    assert!(locked_until.is_some_and(|until| until > Utc::now()));
+
+    // Neither the real password nor the one behind the timing dummy hash
+    // gets through while the lock holds
+    for password in [PASSWORD, "dummy-password"] {
+        let result = login(&mut conn, &email, password);
+        assert!(
+            matches!(result, Err(AppError::Unauthorized(_))),
+            "{} should be rejected",
+            password
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn unknown_email_does_not_accept_the_dummy_password() {
+    let mut conn = test_connection();
+
+    let result = login(&mut conn, "nobody@example.com", "dummy-password");
+    assert!(matches!(result, Err(AppError::Unauthorized(_))));
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use common::{
+    date, insert_room, insert_room_type, insert_staff, reservation_request, test_connection,
+};
+use diesel::prelude::*;
+use my_rooms_be::models::reservation::ReservationStatus;
+use my_rooms_be::models::room::RoomAvailabilityParams;
+use my_rooms_be::schema::reservations;
+use my_rooms_be::services::reservation_service::{
+    create_reservation, update_reservation_status_by_id,
+};
+use my_rooms_be::services::room_service::search_available_rooms;
+use my_rooms_be::utils::common::AppError;
+
+struct Fixture {
+    staff_id: i32,
+    type_id: i32,
+    room_id: i32,
+}
+
+fn fixture(conn: &mut PgConnection) -> Fixture {
+    let staff_id = insert_staff(conn);
+    let type_id = insert_room_type(conn, staff_id);
+    let room_id = insert_room(conn, type_id, staff_id);
+
+    Fixture {
+        staff_id,
+        type_id,
+        room_id,
+    }
+}
+
+// Books 2030-01-10 to 2030-01-15 and walks the reservation to the given status
+fn book_with_status(conn: &mut PgConnection, fixture: &Fixture, status: ReservationStatus) {
+    let mut request = reservation_request(fixture.room_id, "2030-01-10", "2030-01-15");
+    if status == ReservationStatus::Pending {
+        request.status = ReservationStatus::Pending;
+    }
+    create_reservation(conn, &request, fixture.staff_id).unwrap();
+
+    let reservation_id = reservations::table
+        .filter(reservations::room_id.eq(fixture.room_id))
+        .select(reservations::id)
+        .first::<i32>(conn)
+        .unwrap();
+
+    let steps: &[ReservationStatus] = match status {
+        ReservationStatus::Pending | ReservationStatus::Confirmed => &[],
+        ReservationStatus::CheckedIn => &[ReservationStatus::CheckedIn],
+        ReservationStatus::Cancelled => &[ReservationStatus::Cancelled],
+        ReservationStatus::NoShow => &[ReservationStatus::NoShow],
+        ReservationStatus::CheckedOut => {
+            &[ReservationStatus::CheckedIn, ReservationStatus::CheckedOut]
+        }
+    };
+    for step in steps {
+        update_reservation_status_by_id(conn, reservation_id, *step, None, fixture.staff_id)
+            .unwrap();
+    }
+}
+
+fn is_room_available(
+    conn: &mut PgConnection,
+    fixture: &Fixture,
+    check_in: &str,
+    check_out: &str,
+) -> bool {
+    let params = RoomAvailabilityParams {
+        check_in: date(check_in),
+        check_out: date(check_out),
+        guests: None,
+        type_id: Some(fixture.type_id),
+    };
+
+    search_available_rooms(conn, &params)
+        .unwrap()
+        .iter()
+        .flat_map(|group| &group.rooms)
+        .any(|room| room.id == fixture.room_id)
+}
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn cancelled_and_no_show_stays_do_not_block_the_room() {
+    for status in [ReservationStatus::Cancelled, ReservationStatus::NoShow] {
+        let mut conn = test_connection();
+        let fixture = fixture(&mut conn);
+        book_with_status(&mut conn, &fixture, status);
+
+        assert!(
+            is_room_available(&mut conn, &fixture, "2030-01-12", "2030-01-14"),
+            "{} stay should not hide the room",
+            status
+        );
+
+        let request = reservation_request(fixture.room_id, "2030-01-12", "2030-01-14");
+        create_reservation(&mut conn, &request, fixture.staff_id)
+            .unwrap_or_else(|err| panic!("{} stay should not block booking: {}", status, err));
+    }
+}
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn pending_confirmed_and_checked_in_stays_block_the_room() {
+    for status in ReservationStatus::HOLDING_INVENTORY {
+        let mut conn = test_connection();
+        let fixture = fixture(&mut conn);
+        book_with_status(&mut conn, &fixture, status);
+
+        assert!(
+            !is_room_available(&mut conn, &fixture, "2030-01-12", "2030-01-14"),
+            "{} stay should hide the room",
+            status
+        );
+
+        let request = reservation_request(fixture.room_id, "2030-01-12", "2030-01-14");
+        let result = create_reservation(&mut conn, &request, fixture.staff_id);
+        assert!(
+            matches!(result, Err(AppError::Conflict(_))),
+            "{} stay should block booking, got {:?}",
+            status,
+            result
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn back_to_back_stays_are_allowed() {
+    let mut conn = test_connection();
+    let fixture = fixture(&mut conn);
+    book_with_status(&mut conn, &fixture, ReservationStatus::Confirmed);
+
+    // Leaves on the 15th, the next guest arrives the same day
+    assert!(is_room_available(
+        &mut conn,
+        &fixture,
+        "2030-01-15",
+        "2030-01-18"
+    ));
+    let after = reservation_request(fixture.room_id, "2030-01-15", "2030-01-18");
+    create_reservation(&mut conn, &after, fixture.staff_id).unwrap();
+
+    // And a stay ending on the 10th makes way for the one arriving that day
+    assert!(is_room_available(
+        &mut conn,
+        &fixture,
+        "2030-01-07",
+        "2030-01-10"
+    ));
+    let before = reservation_request(fixture.room_id, "2030-01-07", "2030-01-10");
+    create_reservation(&mut conn, &before, fixture.staff_id).unwrap();
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use common::{
+    insert_room, insert_room_type, insert_staff, reservation_request, test_connection, unique,
+};
+use diesel::prelude::*;
+use my_rooms_be::models::room::CreateOrUpdateRoomTypesRequest;
+use my_rooms_be::schema::{customer_contacts, reservations, room_types, rooms};
+use my_rooms_be::services::reservation_service::{create_reservation, update_reservation_by_id};
+use my_rooms_be::services::room_service::{create_room_type, update_room_type_by_id};
+use my_rooms_be::utils::common::AppError;
+
+const UNKNOWN_ROOM_ID: i32 = -1;
+
+fn contact_exists(conn: &mut PgConnection, email: &str) -> bool {
+    customer_contacts::table
+        .filter(customer_contacts::email.eq(email))
+        .count()
+        .get_result::<i64>(conn)
+        .unwrap()
+        > 0
+}
+
+fn room_type_of(conn: &mut PgConnection, room_id: i32) -> Option<i32> {
+    rooms::table
+        .find(room_id)
+        .select(rooms::type_id)
+        .first(conn)
+        .unwrap()
+}
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn failed_reservation_insert_leaves_no_new_contact() {
+    let mut conn = test_connection();
+    let staff_id = insert_staff(&mut conn);
+    let type_id = insert_room_type(&mut conn, staff_id);
+    let room_id = insert_room(&mut conn, type_id, staff_id);
+
+    let first = reservation_request(room_id, "2030-02-01", "2030-02-05");
+    create_reservation(&mut conn, &first, staff_id).unwrap();
+
+    // The contact is written first, then the clashing stay trips the
+    // exclusion constraint
+    let clashing = reservation_request(room_id, "2030-02-03", "2030-02-06");
+    let result = create_reservation(&mut conn, &clashing, staff_id);
+
+    assert!(matches!(result, Err(AppError::Conflict(_))), "{:?}", result);
+    assert!(!contact_exists(&mut conn, clashing.email.as_ref().unwrap()));
+}
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn failed_reservation_update_leaves_no_new_contact() {
+    let mut conn = test_connection();
+    let staff_id = insert_staff(&mut conn);
+    let type_id = insert_room_type(&mut conn, staff_id);
+    let room_id = insert_room(&mut conn, type_id, staff_id);
+
+    let first = reservation_request(room_id, "2030-02-01", "2030-02-05");
+    create_reservation(&mut conn, &first, staff_id).unwrap();
+    let second = reservation_request(room_id, "2030-02-10", "2030-02-12");
+    create_reservation(&mut conn, &second, staff_id).unwrap();
+
+    let second_id = reservations::table
+        .filter(reservations::room_id.eq(room_id))
+        .filter(reservations::check_in_date.eq(second.check_in_date))
+        .select(reservations::id)
+        .first::<i32>(&mut conn)
+        .unwrap();
+
+    // Moved onto the first stay's dates and handed to a new guest
+    let moved = reservation_request(room_id, "2030-02-02", "2030-02-04");
+    let result = update_reservation_by_id(&mut conn, second_id, &moved, staff_id);
+
+    assert!(matches!(result, Err(AppError::Conflict(_))), "{:?}", result);
+    assert!(!contact_exists(&mut conn, moved.email.as_ref().unwrap()));
+}
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn failed_room_type_create_leaves_rooms_untouched() {
+    let mut conn = test_connection();
+    let staff_id = insert_staff(&mut conn);
+    let type_id = insert_room_type(&mut conn, staff_id);
+    let room_id = insert_room(&mut conn, type_id, staff_id);
+
+    let request = CreateOrUpdateRoomTypesRequest {
+        type_name: unique("Type"),
+        description: None,
+        price_per_night: 150,
+        room_ids: Some(vec![room_id, UNKNOWN_ROOM_ID]),
+    };
+    let result = create_room_type(&mut conn, &request, staff_id);
+
+    assert!(
+        matches!(result, Err(AppError::BadRequest(_))),
+        "{:?}",
+        result
+    );
+    let created = room_types::table
+        .filter(room_types::type_name.eq(&request.type_name))
+        .count()
+        .get_result::<i64>(&mut conn)
+        .unwrap();
+    assert_eq!(created, 0);
+    assert_eq!(room_type_of(&mut conn, room_id), Some(type_id));
+}
+
+#[test]
+#[ignore = "needs TEST_DATABASE_URL"]
+fn failed_room_type_update_leaves_type_and_rooms_untouched() {
+    let mut conn = test_connection();
+    let staff_id = insert_staff(&mut conn);
+    let type_id = insert_room_type(&mut conn, staff_id);
+    let other_type_id = insert_room_type(&mut conn, staff_id);
+    let room_id = insert_room(&mut conn, type_id, staff_id);
+
+    let original_name = room_types::table
+        .find(other_type_id)
+        .select(room_types::type_name)
+        .first::<String>(&mut conn)
+        .unwrap();
+
+    // The rename is written first, then the unknown room fails the assignment
+    let request = CreateOrUpdateRoomTypesRequest {
+        type_name: unique("Renamed"),
+        description: None,
+        price_per_night: 150,
+        room_ids: Some(vec![room_id, UNKNOWN_ROOM_ID]),
+    };
+    let result = update_room_type_by_id(&mut conn, other_type_id, &request, staff_id);
+
+    assert!(
+        matches!(result, Err(AppError::BadRequest(_))),
+        "{:?}",
+        result
+    );
+    let name = room_types::table
+        .find(other_type_id)
+        .select(room_types::type_name)
+        .first::<String>(&mut conn)
+        .unwrap();
+    assert_eq!(name, original_name);
+    assert_eq!(room_type_of(&mut conn, room_id), Some(type_id));
+}